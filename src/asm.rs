@@ -0,0 +1,426 @@
+use crate::{
+    ast::*,
+    error::{SError, SyntaxError},
+    expression::{expect_not_bit_field, expect_scalar},
+    state::{SResult, State},
+    token::TokenType,
+    ty::Type,
+    utils::Pos,
+};
+
+/// a general purpose register an operand of an `asm` statement may be given: any but
+/// `%rbp` and `%rsp`, which hold the frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Register {
+    Rax,
+    Rbx,
+    Rcx,
+    Rdx,
+    Rsi,
+    Rdi,
+    R8,
+    R9,
+    R10,
+    R11,
+    R12,
+    R13,
+    R14,
+    R15,
+}
+
+/// names of the registers by size: 8, 4, 2 and 1 bytes.
+const NAMES: [[&str; 4]; 14] = [
+    ["%rax", "%eax", "%ax", "%al"],
+    ["%rbx", "%ebx", "%bx", "%bl"],
+    ["%rcx", "%ecx", "%cx", "%cl"],
+    ["%rdx", "%edx", "%dx", "%dl"],
+    ["%rsi", "%esi", "%si", "%sil"],
+    ["%rdi", "%edi", "%di", "%dil"],
+    ["%r8", "%r8d", "%r8w", "%r8b"],
+    ["%r9", "%r9d", "%r9w", "%r9b"],
+    ["%r10", "%r10d", "%r10w", "%r10b"],
+    ["%r11", "%r11d", "%r11w", "%r11b"],
+    ["%r12", "%r12d", "%r12w", "%r12b"],
+    ["%r13", "%r13d", "%r13w", "%r13b"],
+    ["%r14", "%r14d", "%r14w", "%r14b"],
+    ["%r15", "%r15d", "%r15w", "%r15b"],
+];
+
+/// the registers `r` operands are given, the caller-saved ones first.
+const POOL: [Register; 14] = {
+    use Register::*;
+    [
+        Rax, Rcx, Rdx, Rsi, Rdi, R8, R9, R10, R11, Rbx, R12, R13, R14, R15,
+    ]
+};
+
+impl Register {
+    pub fn name(self, size: usize) -> &'static str {
+        let names = &NAMES[self as usize];
+        match size {
+            1 => names[3],
+            2 => names[2],
+            4 => names[1],
+            _ => names[0],
+        }
+    }
+
+    /// the register named `name` in any size, with or without the `%`.
+    fn from_name(name: &str) -> Option<Register> {
+        let name = name.strip_prefix('%').unwrap_or(name);
+        let index = NAMES
+            .iter()
+            .position(|names| names.iter().any(|n| &n[1..] == name))?;
+        Some(POOL.into_iter().find(|reg| *reg as usize == index).unwrap())
+    }
+
+    /// the single letter constraints of `%rax`, `%rbx`, `%rcx`, `%rdx`, `%rsi` and `%rdi`.
+    fn from_constraint(c: char) -> Option<Register> {
+        match c {
+            'a' => Some(Register::Rax),
+            'b' => Some(Register::Rbx),
+            'c' => Some(Register::Rcx),
+            'd' => Some(Register::Rdx),
+            'S' => Some(Register::Rsi),
+            'D' => Some(Register::Rdi),
+            _ => None,
+        }
+    }
+
+    /// whether a function has to preserve the register for its caller.
+    pub fn is_callee_saved(self) -> bool {
+        use Register::*;
+        matches!(self, Rbx | R12 | R13 | R14 | R15)
+    }
+}
+
+/// where the constraint of an operand puts it.
+enum Place {
+    /// any register of the pool.
+    Any,
+    Fixed(Register),
+    /// memory addressed through a register of the pool.
+    Memory,
+    Immediate,
+    /// the register of the output with the index.
+    Matching(usize),
+}
+
+/// an operand as written, before it is given a register.
+struct Operand {
+    name: Option<String>,
+    place: Place,
+    expr: Expr,
+    is_output: bool,
+    /// an output which is read too, for `+`.
+    is_read: bool,
+}
+
+/// how an operand is printed in the assembly.
+enum Printed {
+    Register(Register, usize),
+    Memory(Register),
+    Immediate(i64),
+}
+
+impl State {
+    /// asm = ("asm" | "__asm__" | "__asm") "volatile"? "(" string+
+    ///       (":" operands? (":" operands? (":" clobbers?)?)?)? ")" ";"
+    ///
+    /// without operands the assembly is emitted as is, otherwise its `%` are substituted.
+    pub(crate) fn parse_asm_statement(&mut self) -> SResult<AsmStmt> {
+        let start = self.cur_token_start();
+        self.next()?;
+        self.eat(&TokenType::Volatile)?;
+        self.expect(&TokenType::ParenL)?;
+        let template_start = self.cur_token_start();
+        let template = self.parse_asm_string()?;
+        if !self.eat(&TokenType::Colon)? {
+            self.expect(&TokenType::ParenR)?;
+            self.expect(&TokenType::Semi)?;
+            return Ok(AsmStmt {
+                loc: self.finish_loc(start),
+                text: template,
+                operands: vec![],
+                saved: vec![],
+            });
+        }
+
+        let mut operands = self.parse_asm_operands(true)?;
+        let outputs = operands.len();
+        let mut used = vec![];
+        if self.eat(&TokenType::Colon)? {
+            operands.extend(self.parse_asm_operands(false)?);
+            if self.eat(&TokenType::Colon)? {
+                used = self.parse_asm_clobbers()?;
+            }
+        }
+        self.expect(&TokenType::ParenR)?;
+        self.expect(&TokenType::Semi)?;
+
+        // registers asked for by a constraint or clobbered are not given to other operands.
+        // An input may be asked for in the register of an output, which is written after
+        // it is read.
+        let clobbers = used.len();
+        for (index, operand) in operands.iter().enumerate() {
+            if let Place::Fixed(reg) = operand.place {
+                let is_input_of_output = index >= outputs
+                    && operands[..outputs].iter().any(|output| {
+                        !output.is_read && matches!(output.place, Place::Fixed(r) if r == reg)
+                    });
+                if used[..clobbers].contains(&reg) || (used.contains(&reg) && !is_input_of_output) {
+                    return Err(invalid_asm(&operand.expr));
+                }
+                used.push(reg);
+            }
+        }
+        let names: Vec<Option<String>> = operands.iter().map(|op| op.name.clone()).collect();
+        let mut printed = vec![];
+        let mut asm_operands = vec![];
+        for operand in operands {
+            let reg = match operand.place {
+                Place::Fixed(reg) => reg,
+                Place::Any | Place::Memory => {
+                    let reg = POOL.into_iter().find(|reg| !used.contains(reg));
+                    let reg = reg.ok_or_else(|| invalid_asm(&operand.expr))?;
+                    used.push(reg);
+                    reg
+                }
+                Place::Matching(index) => match printed.get(index) {
+                    Some(Printed::Register(reg, _)) if index < outputs => *reg,
+                    _ => return Err(invalid_asm(&operand.expr)),
+                },
+                Place::Immediate => {
+                    printed.push(Printed::Immediate(self.eval_const(&operand.expr)?));
+                    continue;
+                }
+            };
+            let (kind, expr) = match operand.place {
+                Place::Memory => (AsmKind::Memory, address_of(operand.expr)?),
+                _ if operand.is_output => {
+                    expect_scalar(&operand.expr)?;
+                    if operand.expr.ty().has_const() {
+                        return Err(SError::new(
+                            operand.expr.loc().get_start().clone(),
+                            SyntaxError::AssignToConst,
+                        ));
+                    }
+                    let kind = AsmKind::Output {
+                        is_read: operand.is_read,
+                    };
+                    (kind, address_of(operand.expr)?)
+                }
+                _ => {
+                    expect_scalar(&operand.expr)?;
+                    (AsmKind::Input, operand.expr)
+                }
+            };
+            let ty = match &expr {
+                Expr::Addr(addr) if kind != AsmKind::Input => addr.argument.ty().clone(),
+                expr => expr.ty().decay(),
+            };
+            printed.push(match kind {
+                AsmKind::Memory => Printed::Memory(reg),
+                _ => Printed::Register(reg, ty.size()),
+            });
+            asm_operands.push(AsmOperand {
+                kind,
+                expr,
+                slot: self.locals.declare_anonymous(Type::ulong()).offset,
+                ty,
+                reg,
+            });
+        }
+
+        let text = render(&template, &printed, &names, template_start)?;
+        let saved = POOL
+            .into_iter()
+            .filter(|reg| reg.is_callee_saved() && used.contains(reg))
+            .collect();
+        Ok(AsmStmt {
+            loc: self.finish_loc(start),
+            text,
+            operands: asm_operands,
+            saved,
+        })
+    }
+
+    /// operands = (operand ("," operand)*)?
+    /// operand = ("[" ident "]")? string "(" expr ")"
+    fn parse_asm_operands(&mut self, is_output: bool) -> SResult<Vec<Operand>> {
+        let mut operands = vec![];
+        if !matches!(
+            self.cur_token().get_type(),
+            TokenType::Str(_) | TokenType::BracketL
+        ) {
+            return Ok(operands);
+        }
+        loop {
+            let name = if self.eat(&TokenType::BracketL)? {
+                let name = match self.cur_token().get_type() {
+                    TokenType::Name(name) => name.clone(),
+                    _ => return self.unexpected(self.cur_token()),
+                };
+                self.next()?;
+                self.expect(&TokenType::BracketR)?;
+                Some(name)
+            } else {
+                None
+            };
+            let start = self.cur_token_start();
+            let constraint = self.parse_asm_string()?;
+            let (place, is_read) = parse_constraint(&constraint, is_output)
+                .ok_or_else(|| SError::new(start, SyntaxError::InvalidAsm))?;
+            let expr = self.parse_paren_expr()?;
+            operands.push(Operand {
+                name,
+                place,
+                expr,
+                is_output,
+                is_read,
+            });
+            if !self.eat(&TokenType::Comma)? {
+                return Ok(operands);
+            }
+        }
+    }
+
+    /// clobbers = (string ("," string)*)?
+    ///
+    /// `memory` and `cc` need nothing: no value is kept in a register across statements,
+    /// nor are flags.
+    fn parse_asm_clobbers(&mut self) -> SResult<Vec<Register>> {
+        let mut clobbers = vec![];
+        while let TokenType::Str(_) = self.cur_token().get_type() {
+            let start = self.cur_token_start();
+            let clobber = self.parse_asm_string()?;
+            match clobber.as_str() {
+                "memory" | "cc" => {}
+                name => match Register::from_name(name) {
+                    Some(reg) => clobbers.push(reg),
+                    None => return Err(SError::new(start, SyntaxError::InvalidAsm)),
+                },
+            }
+            if !self.eat(&TokenType::Comma)? {
+                break;
+            }
+        }
+        Ok(clobbers)
+    }
+
+    /// adjacent string literals, concatenated.
+    fn parse_asm_string(&mut self) -> SResult<String> {
+        let mut string = String::new();
+        let mut is_empty = true;
+        while let TokenType::Str(bytes) = self.cur_token().get_type() {
+            string.push_str(&String::from_utf8_lossy(bytes));
+            is_empty = false;
+            self.next()?;
+        }
+        if is_empty {
+            return self.unexpected(self.cur_token());
+        }
+        Ok(string)
+    }
+}
+
+/// where a constraint puts an operand, and whether an output is read too. An output
+/// starts with `=`, or `+` to be read too. `&` is ignored, as no two operands share a
+/// register but for a matching constraint.
+fn parse_constraint(constraint: &str, is_output: bool) -> Option<(Place, bool)> {
+    let mut chars = constraint.chars();
+    let is_read = match is_output {
+        true => match chars.next()? {
+            '=' => false,
+            '+' => true,
+            _ => return None,
+        },
+        false => false,
+    };
+    let place = match chars.find(|&c| c != '&')? {
+        'r' | 'q' | 'g' => Place::Any,
+        'm' => Place::Memory,
+        'i' | 'n' if !is_output => Place::Immediate,
+        c @ '0'..='9' if !is_output => Place::Matching(c as usize - '0' as usize),
+        c => Place::Fixed(Register::from_constraint(c)?),
+    };
+    Some((place, is_read))
+}
+
+/// the address of the lvalue of an output or a memory operand.
+fn address_of(expr: Expr) -> SResult<Expr> {
+    expect_not_bit_field(&expr)?;
+    if !matches!(expr, Expr::Ident(_) | Expr::Deref(_) | Expr::Member(_)) {
+        return Err(SError::new(
+            expr.loc().get_start().clone(),
+            SyntaxError::CastWrong,
+        ));
+    }
+    Ok(Expr::Addr(AddrExpr {
+        loc: expr.loc().clone(),
+        ty: Type::pointer_to(expr.ty().clone()),
+        argument: Box::new(expr),
+    }))
+}
+
+fn invalid_asm(expr: &Expr) -> SError {
+    SError::new(expr.loc().get_start().clone(), SyntaxError::InvalidAsm)
+}
+
+/// substitute the operands of `template`: `%N` or `%[name]` print an operand, with the
+/// modifier `b`, `w`, `k` or `q` a register of that size, and with `c` an immediate
+/// without its `$`. `%%` prints a `%`.
+fn render(
+    template: &str,
+    printed: &[Printed],
+    names: &[Option<String>],
+    start: Pos,
+) -> SResult<String> {
+    let invalid = || SError::new(start.clone(), SyntaxError::InvalidAsm);
+    let mut text = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            text.push(c);
+            continue;
+        }
+        if chars.next_if_eq(&'%').is_some() {
+            text.push('%');
+            continue;
+        }
+        let modifier = chars.next_if(|c| "bwkqc".contains(*c));
+        let index = if chars.next_if_eq(&'[').is_some() {
+            let name: String = chars.by_ref().take_while(|&c| c != ']').collect();
+            names
+                .iter()
+                .position(|n| n.as_deref() == Some(name.as_str()))
+        } else {
+            let mut digits = String::new();
+            while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                digits.push(digit);
+            }
+            digits.parse::<usize>().ok()
+        };
+        let operand = index
+            .and_then(|index| printed.get(index))
+            .ok_or_else(invalid)?;
+        let size = |modifier| match modifier {
+            'b' => Some(1),
+            'w' => Some(2),
+            'k' => Some(4),
+            'q' => Some(8),
+            _ => None,
+        };
+        match (operand, modifier) {
+            (Printed::Register(reg, size), None) => text.push_str(reg.name(*size)),
+            (Printed::Register(reg, _), Some(modifier)) => {
+                text.push_str(reg.name(size(modifier).ok_or_else(invalid)?))
+            }
+            (Printed::Memory(reg), None) => text.push_str(&format!("({})", reg.name(8))),
+            (Printed::Immediate(value), None) => text.push_str(&format!("${}", value)),
+            (Printed::Immediate(value), Some('c')) => text.push_str(&value.to_string()),
+            _ => return Err(invalid()),
+        }
+    }
+    Ok(text)
+}
@@ -1,8 +1,14 @@
-use crate::{object::Offset, token::TokenType, utils::Loc};
+use crate::{
+    layout::BitField,
+    object::{Offset, Storage},
+    token::TokenType,
+    ty::Type,
+    utils::Loc,
+};
 
 use super::Lit;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum Expr {
     Binary(BinaryExpr),
     Literal(Lit),
@@ -11,20 +17,17 @@ pub enum Expr {
     Ident(IdentExpr),
     Deref(DerefExpr),
     Addr(AddrExpr),
+    Cast(CastExpr),
+    Member(MemberExpr),
+    MemZero(MemZeroExpr),
+    CompoundLit(CompoundLitExpr),
+    Call(CallExpr),
+    VaStart(VaStartExpr),
+    VaArg(VaArgExpr),
+    Alloca(AllocaExpr),
 }
 
 impl Expr {
-    pub fn is_addr(&self) -> bool {
-        matches!(self, Expr::Addr(_))
-    }
-
-    pub fn as_binary(&self) -> Option<&BinaryExpr> {
-        match self {
-            Expr::Binary(expr) => Some(expr),
-            _ => None,
-        }
-    }
-
     // pub fn as_lit(&self) -> Option<&Lit> {
     //     match self {
     //         Expr::Literal(lit) => Some(lit),
@@ -62,37 +65,68 @@ impl Expr {
             Expr::Ident(expr) => expr.loc.clone(),
             Expr::Deref(expr) => expr.loc.clone(),
             Expr::Addr(expr) => expr.loc.clone(),
+            Expr::Cast(expr) => expr.loc.clone(),
+            Expr::Member(expr) => expr.loc.clone(),
+            Expr::MemZero(expr) => expr.loc.clone(),
+            Expr::CompoundLit(expr) => expr.loc.clone(),
+            Expr::Call(expr) => expr.loc.clone(),
+            Expr::VaStart(expr) => expr.loc.clone(),
+            Expr::VaArg(expr) => expr.loc.clone(),
+            Expr::Alloca(expr) => expr.loc.clone(),
+        }
+    }
+
+    pub fn ty(&self) -> &Type {
+        match self {
+            Expr::Binary(expr) => &expr.ty,
+            Expr::Literal(expr) => expr.ty(),
+            Expr::Unary(expr) => &expr.ty,
+            Expr::Assign(expr) => &expr.ty,
+            Expr::Ident(expr) => &expr.ty,
+            Expr::Deref(expr) => &expr.ty,
+            Expr::Addr(expr) => &expr.ty,
+            Expr::Cast(expr) => &expr.ty,
+            Expr::Member(expr) => &expr.ty,
+            Expr::MemZero(expr) => &expr.ty,
+            Expr::CompoundLit(expr) => &expr.ty,
+            Expr::Call(expr) => &expr.ty,
+            Expr::VaStart(expr) => &expr.ty,
+            Expr::VaArg(expr) => &expr.ty,
+            Expr::Alloca(expr) => &expr.ty,
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct IdentExpr {
     pub loc: Loc,
-    pub name: String,
-    pub offset: Offset,
+    pub storage: Storage,
+    pub ty: Type,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum LeftVal {
     Ident(IdentExpr),
     Deref(DerefExpr),
+    Member(MemberExpr),
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct AssignExpr {
     pub loc: Loc,
     // TODO: left_val
     pub left: Box<LeftVal>,
     pub right: Box<Expr>,
+    pub ty: Type,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct BinaryExpr {
     pub loc: Loc,
     pub left: Box<Expr>,
     pub op: BinaryOp,
     pub right: Box<Expr>,
+    pub ty: Type,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -122,22 +156,109 @@ pub enum BinaryOp {
     GreatEqual,
 }
 
-#[derive(Debug)]
+impl BinaryOp {
+    pub const fn is_comparison(&self) -> bool {
+        use BinaryOp::*;
+        matches!(
+            self,
+            Equal | NotEqual | Less | LessEqual | Great | GreatEqual
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct UnaryExpr {
     pub loc: Loc,
     pub op: TokenType,
     pub argument: Box<Expr>,
-    pub prefix: bool,
+    pub ty: Type,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct DerefExpr {
     pub loc: Loc,
     pub argument: Box<Expr>,
+    pub ty: Type,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct AddrExpr {
     pub loc: Loc,
     pub argument: Box<Expr>,
+    pub ty: Type,
+}
+
+/// an explicit `(type)expr`, or a conversion implied by the operator around `argument`.
+#[derive(Clone, Debug)]
+pub struct CastExpr {
+    pub loc: Loc,
+    pub argument: Box<Expr>,
+    pub ty: Type,
+}
+
+/// `base.name`, where `p->name` is parsed as `(*p).name`.
+#[derive(Clone, Debug)]
+pub struct MemberExpr {
+    pub loc: Loc,
+    pub base: Box<Expr>,
+    pub offset: usize,
+    pub bit_field: Option<BitField>,
+    pub ty: Type,
+}
+
+/// clear a local to zero before the initializers of some of its elements or members run.
+#[derive(Clone, Debug)]
+pub struct MemZeroExpr {
+    pub loc: Loc,
+    pub target: IdentExpr,
+    pub ty: Type,
+}
+
+/// `(type){...}` in a block, an unnamed local which `inits` initialize before it is used.
+#[derive(Clone, Debug)]
+pub struct CompoundLitExpr {
+    pub loc: Loc,
+    pub inits: Vec<Expr>,
+    pub var: IdentExpr,
+    pub ty: Type,
+}
+
+/// `callee(args...)`, where `callee` has a function type: a function, or a dereferenced pointer to one.
+#[derive(Clone, Debug)]
+pub struct CallExpr {
+    pub loc: Loc,
+    pub callee: Box<Expr>,
+    pub args: Vec<Expr>,
+    /// the local a returned record is stored to, which is the value of the call.
+    pub ret_buffer: Option<Offset>,
+    pub ty: Type,
+}
+
+/// `__builtin_va_start(ap, last)`, which points the `va_list` at `ap` to the variadic arguments.
+#[derive(Clone, Debug)]
+pub struct VaStartExpr {
+    pub loc: Loc,
+    pub ap: Box<Expr>,
+    /// the register save area of the function.
+    pub area: Offset,
+    pub ty: Type,
+}
+
+/// `__builtin_va_arg(ap, ty)`, the next variadic argument read as `ty`.
+#[derive(Clone, Debug)]
+pub struct VaArgExpr {
+    pub loc: Loc,
+    pub ap: Box<Expr>,
+    pub ty: Type,
+}
+
+/// `__builtin_alloca(size)`, and the storage of a variable-length array: `size` bytes
+/// taken from the stack, below the locals of the function and any earlier allocation.
+#[derive(Clone, Debug)]
+pub struct AllocaExpr {
+    pub loc: Loc,
+    pub size: Box<Expr>,
+    /// the local holding the bottom of the allocations, above the values being computed.
+    pub bottom: Offset,
+    pub ty: Type,
 }
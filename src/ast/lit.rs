@@ -1,27 +1,34 @@
-use crate::utils::Loc;
+use crate::{ty::Type, utils::Loc};
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum Lit {
-    Int32(Int32Lit),
+    Int(IntLit),
 }
 
 impl Lit {
-    // pub fn as_int32(&self) -> Option<&Int32Lit> {
+    // pub fn as_int(&self) -> Option<&IntLit> {
     //     match self {
-    //         Lit::Int32(lit) => Some(lit),
+    //         Lit::Int(lit) => Some(lit),
     //         _ => None,
     //     }
     // }
 
     pub fn loc(&self) -> Loc {
         match self {
-            Lit::Int32(lit) => lit.loc.clone(),
+            Lit::Int(lit) => lit.loc.clone(),
+        }
+    }
+
+    pub fn ty(&self) -> &Type {
+        match self {
+            Lit::Int(lit) => &lit.ty,
         }
     }
 }
 
-#[derive(Debug)]
-pub struct Int32Lit {
+#[derive(Clone, Debug)]
+pub struct IntLit {
     pub loc: Loc,
     pub num: String,
+    pub ty: Type,
 }
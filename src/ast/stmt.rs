@@ -1,4 +1,12 @@
-use crate::{ast::Expr, utils::Loc};
+use std::collections::HashMap;
+
+use crate::{
+    asm::Register,
+    ast::Expr,
+    object::{Global, Offset, Var},
+    ty::Type,
+    utils::Loc,
+};
 
 #[derive(Debug)]
 pub enum Stmt {
@@ -7,14 +15,126 @@ pub enum Stmt {
     Block(BlockStmt),
     Empty(EmptyStmt),
     If(IfStmt),
-    For(ForStmt),
+    For(Box<ForStmt>),
     While(WhileStmt),
+    Switch(SwitchStmt),
+    Case(CaseStmt),
+    Default(DefaultStmt),
+    Break(BreakStmt),
+    Labeled(LabeledStmt),
+    Goto(GotoStmt),
+    Decl(DeclStmt),
+    Asm(AsmStmt),
+}
+
+impl Stmt {
+    pub fn loc(&self) -> &Loc {
+        match self {
+            Stmt::Expr(stmt) => &stmt.loc,
+            Stmt::Return(stmt) => &stmt.loc,
+            Stmt::Block(stmt) => &stmt.loc,
+            Stmt::Empty(stmt) => &stmt.loc,
+            Stmt::If(stmt) => &stmt.loc,
+            Stmt::For(stmt) => &stmt.loc,
+            Stmt::While(stmt) => &stmt.loc,
+            Stmt::Switch(stmt) => &stmt.loc,
+            Stmt::Case(stmt) => &stmt.loc,
+            Stmt::Default(stmt) => &stmt.loc,
+            Stmt::Break(stmt) => &stmt.loc,
+            Stmt::Labeled(stmt) => &stmt.loc,
+            Stmt::Goto(stmt) => &stmt.loc,
+            Stmt::Decl(stmt) => &stmt.loc,
+            Stmt::Asm(stmt) => &stmt.loc,
+        }
+    }
+}
+
+/// an `asm` statement, whose operands are loaded to their registers before the assembly
+/// and the outputs stored from theirs after it.
+#[derive(Debug)]
+pub struct AsmStmt {
+    pub loc: Loc,
+    /// the assembly, with the operands substituted.
+    pub text: String,
+    /// the operands in registers or in memory, in order, but for the immediates.
+    pub operands: Vec<AsmOperand>,
+    /// the callee-saved registers the assembly uses or clobbers, which are saved around it.
+    pub saved: Vec<Register>,
+}
+
+#[derive(Debug)]
+pub struct AsmOperand {
+    pub kind: AsmKind,
+    /// the address of an output or a memory operand, or the value of an input.
+    pub expr: Expr,
+    /// the local `expr` is kept in until the registers are loaded.
+    pub slot: Offset,
+    /// the type of the value in the register, or of the object in memory.
+    pub ty: Type,
+    pub reg: Register,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AsmKind {
+    /// an output in a register, which holds its value before the assembly too if read.
+    Output { is_read: bool },
+    /// an input in a register.
+    Input,
+    /// an output or an input in memory, addressed through the register.
+    Memory,
+}
+
+/// declaration of locals, run as the assignments of their initializers.
+#[derive(Debug)]
+pub struct DeclStmt {
+    pub loc: Loc,
+    pub inits: Vec<Expr>,
+}
+
+#[derive(Debug)]
+pub struct LabeledStmt {
+    pub loc: Loc,
+    pub label: String,
+    pub body: Box<Stmt>,
+}
+
+#[derive(Debug)]
+pub struct GotoStmt {
+    pub loc: Loc,
+    pub label: String,
+    /// the locals the bottom of the allocations is saved to before each variable-length
+    /// array in scope, the first of those out of scope at the label being restored.
+    pub vlas: Vec<Offset>,
+}
+
+#[derive(Debug)]
+pub struct SwitchStmt {
+    pub loc: Loc,
+    pub test: Expr,
+    pub body: Box<Stmt>,
+    /// values of the `case` labels, indexed by `CaseStmt::index`.
+    pub cases: Vec<i64>,
+    pub has_default: bool,
+}
+
+#[derive(Debug)]
+pub struct CaseStmt {
+    pub loc: Loc,
+    /// position of this label in `SwitchStmt::cases`.
+    pub index: usize,
+    pub body: Box<Stmt>,
+}
+
+#[derive(Debug)]
+pub struct DefaultStmt {
+    pub loc: Loc,
+    pub body: Box<Stmt>,
 }
 
 #[derive(Debug)]
 pub struct ForStmt {
     pub loc: Loc,
-    pub init: Option<Expr>,
+    pub init: Option<Box<Stmt>>,
     pub test: Option<Expr>,
     pub update: Option<Expr>,
     pub body: Box<Stmt>,
@@ -40,10 +160,21 @@ pub struct EmptyStmt {
     pub loc: Loc,
 }
 
+#[derive(Debug)]
+pub struct BreakStmt {
+    pub loc: Loc,
+    /// the local the bottom of the allocations is saved to before the first
+    /// variable-length array inside the loop or `switch`, restored to free them all.
+    pub saved_bottom: Option<Offset>,
+}
+
 #[derive(Debug)]
 pub struct BlockStmt {
     pub loc: Loc,
     pub body: Vec<Stmt>,
+    /// the local the bottom of the allocations is saved to where the first variable-length
+    /// array of the block is declared, and restored from on exit, which frees them.
+    pub saved_bottom: Option<Offset>,
 }
 
 #[derive(Debug)]
@@ -59,8 +190,31 @@ pub struct ReturnStmt {
 }
 
 #[derive(Debug)]
-pub struct Program {
+pub struct Function {
     pub loc: Loc,
+    pub name: String,
+    /// `static` functions are not exported with `.globl`.
+    pub is_static: bool,
+    /// the locals the arguments are stored to, in order.
+    pub params: Vec<Var>,
+    pub ret: Type,
+    /// the register save area of a variadic function, which the prologue fills.
+    pub va_area: Option<Offset>,
+    /// the local keeping the address a returned record is stored to, if the caller passes one.
+    pub ret_buffer: Option<Offset>,
+    /// the local holding the bottom of the stack allocations, if the function makes any.
+    pub alloca_bottom: Option<Offset>,
+    /// how many variable-length arrays are in scope at each label.
+    pub labels: HashMap<String, usize>,
     pub body: Vec<Stmt>,
     pub stack_size: usize,
 }
+
+#[derive(Debug)]
+pub struct Program {
+    /// the span of the whole input, which no diagnostic points at yet.
+    #[allow(dead_code)]
+    pub loc: Loc,
+    pub functions: Vec<Function>,
+    pub globals: Vec<Global>,
+}
@@ -1,27 +1,153 @@
+use std::collections::HashMap;
+
 use crate::{
     ast::{
-        AddrExpr, AssignExpr, BinaryExpr, DerefExpr, Expr, IdentExpr, Int32Lit, LeftVal, Lit,
-        UnaryExpr,
+        AddrExpr, AllocaExpr, AssignExpr, BinaryExpr, CallExpr, CastExpr, CompoundLitExpr,
+        DerefExpr, Expr, IdentExpr, IntLit, LeftVal, Lit, MemZeroExpr, MemberExpr, UnaryExpr,
+        VaArgExpr, VaStartExpr,
     },
     ast::{
-        BinaryOp, BlockStmt, EmptyStmt, ExprStmt, ForStmt, IfStmt, Program, ReturnStmt, Stmt,
-        WhileStmt,
+        AsmKind, AsmStmt, BinaryOp, BlockStmt, BreakStmt, CaseStmt, DeclStmt, DefaultStmt,
+        ExprStmt, ForStmt, Function, GotoStmt, IfStmt, LabeledStmt, Program, ReturnStmt, Stmt,
+        SwitchStmt, WhileStmt,
     },
-    head, pop, push, tail,
+    head,
+    layout::BitField,
+    object::{Data, Global, Offset, Storage},
+    pop, push, tail,
     token::TokenType,
+    ty::{Type, TypeKind},
+    utils::align,
 };
 
+/// registers of the first six integer arguments, by size.
+const ARG_REGS_64: [&str; 6] = ["%rdi", "%rsi", "%rdx", "%rcx", "%r8", "%r9"];
+const ARG_REGS_32: [&str; 6] = ["%edi", "%esi", "%edx", "%ecx", "%r8d", "%r9d"];
+const ARG_REGS_16: [&str; 6] = ["%di", "%si", "%dx", "%cx", "%r8w", "%r9w"];
+const ARG_REGS_8: [&str; 6] = ["%dil", "%sil", "%dl", "%cl", "%r8b", "%r9b"];
+
+/// the System V ABI class of an argument or a return value. There are no floating
+/// point types, so no eightbyte is ever of class SSE.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Class {
+    /// in as many general purpose registers as it has eightbytes.
+    Integer(usize),
+    /// a record over 16 bytes, or with unaligned members: copied to the stack as an
+    /// argument, and stored through a hidden pointer passed in `%rdi` as a return value.
+    Memory,
+}
+
+fn classify(ty: &Type) -> Class {
+    if ty.size() > 16 || has_unaligned_member(ty) {
+        Class::Memory
+    } else {
+        Class::Integer(align(ty.size(), 8) / 8)
+    }
+}
+
+/// whether a member of a record, or of a record in it, is not at a multiple of the
+/// alignment of its type, as in a `packed` record.
+fn has_unaligned_member(ty: &Type) -> bool {
+    match &ty.kind {
+        TypeKind::Array(base, _) => has_unaligned_member(base),
+        TypeKind::Record(record) => record.borrow().members.iter().any(|member| {
+            (member.bit_field.is_none() && member.offset % member.ty.align() != 0)
+                || has_unaligned_member(&member.ty)
+        }),
+        _ => false,
+    }
+}
+
+/// where an argument is passed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Place {
+    /// in the argument registers from this index on.
+    Reg(usize),
+    /// at this byte offset in the arguments on the stack.
+    Stack(usize),
+}
+
+/// place the arguments of a function returning `ret` in order: a record goes to the
+/// stack as a whole when it is in memory or not enough registers are left for it.
+/// Returns the places with the number of registers and stack bytes used.
+fn place_args<'a>(args: impl Iterator<Item = &'a Type>, ret: &Type) -> (Vec<Place>, usize, usize) {
+    // the hidden pointer for the return value takes the first register.
+    let mut reg = (ret.record().is_some() && classify(ret) == Class::Memory) as usize;
+    let mut stack = 0;
+    let places = args
+        .map(|ty| match classify(ty) {
+            Class::Integer(n) if reg + n <= ARG_REGS_64.len() => {
+                reg += n;
+                Place::Reg(reg - n)
+            }
+            _ => {
+                stack += align(ty.size(), 8);
+                Place::Stack(stack - align(ty.size(), 8))
+            }
+        })
+        .collect();
+    (places, reg, stack)
+}
+
 pub fn run(program: &Program, context: Context) {
     let mut context = context;
-    for stmt in &program.body {
-        context.statement(stmt);
+    for function in &program.functions {
+        context.function(function);
     }
 
-    println!("{}", head!(program.stack_size));
+    // the input comes from the command line, so it has no file name.
+    println!(".file 1 \"-\"");
+    println!(".text");
     for code in &context.code {
         println!("{}", code);
     }
-    println!("{}", tail!());
+
+    if !context.rodata.is_empty() {
+        println!(".section .rodata");
+        for data in &context.rodata {
+            println!("{}", data);
+        }
+    }
+
+    for global in program.globals.iter() {
+        if global.is_definition && global.ty.func_type().is_none() {
+            data(global);
+        }
+    }
+}
+
+/// emit a global into `.data` when it has an initial value, and into `.bss` otherwise.
+fn data(global: &Global) {
+    let size = global.ty.size();
+    println!(
+        "{}",
+        if global.init.is_some() {
+            ".data"
+        } else {
+            ".bss"
+        }
+    );
+    if !global.is_static {
+        println!(".globl {}", global.name);
+    }
+    println!(".align {}", global.align);
+    println!("{}:", global.name);
+    match &global.init {
+        Some(init) => {
+            for data in init {
+                match data {
+                    Data::Int(value, 1) => println!("    .byte {}", value),
+                    Data::Int(value, 2) => println!("    .short {}", value),
+                    Data::Int(value, 4) => println!("    .long {}", value),
+                    Data::Int(value, _) => println!("    .quad {}", value),
+                    Data::Addr(label, 0) => println!("    .quad {}", label),
+                    Data::Addr(label, addend) => println!("    .quad {}{:+}", label, addend),
+                    Data::Zero(size) => println!("    .zero {}", size),
+                }
+            }
+        }
+        None => println!("    .zero {}", size),
+    }
 }
 
 type Assemble = String;
@@ -32,42 +158,358 @@ pub struct Context {
     /// use for block jump, such as `if-else`, `for-loop`.
     count: usize,
     code: Code,
+    /// read-only data, such as the jump tables of `switch`.
+    rodata: Code,
     stack_size: usize,
+    /// label counts of the enclosing loops and `switch`, jumped to by `break`.
+    breaks: Vec<usize>,
+    /// label counts of the enclosing `switch`, used by `case` and `default`.
+    switches: Vec<usize>,
+    /// label counts of the user defined labels in the current function.
+    labels: HashMap<String, usize>,
+    /// how many variable-length arrays are in scope at each label of the current function.
+    label_vlas: HashMap<String, usize>,
+    /// the name of the current function, which `return` jumps to the end of.
+    function: String,
+    /// the number of values pushed to the stack, which keeps calls 16-byte aligned.
+    depth: usize,
+    /// the local keeping where the current function stores a record it returns in memory.
+    ret_buffer: Option<Offset>,
+    /// the local holding the bottom of the stack allocations of the current function.
+    alloca_bottom: Option<Offset>,
+    /// the registers and stack bytes taken by the named parameters of the current
+    /// function, after which `va_start` finds the variadic arguments.
+    named_args: (usize, usize),
 }
 
 impl Context {
-    pub fn new(stack_size: usize) -> Self {
+    pub fn new() -> Self {
         Self {
             count: 0,
             code: Default::default(),
-            stack_size,
+            rodata: Default::default(),
+            stack_size: 0,
+            breaks: Default::default(),
+            switches: Default::default(),
+            labels: Default::default(),
+            function: Default::default(),
+            depth: 0,
+            ret_buffer: None,
+            alloca_bottom: None,
+            label_vlas: HashMap::new(),
+            named_args: (0, 0),
         }
     }
 
+    fn push(&mut self) {
+        self.code.push(push!());
+        self.depth += 1;
+    }
+
+    fn pop(&mut self, reg: &str) {
+        self.code.push(pop!(reg));
+        self.depth -= 1;
+    }
+
+    fn function(&mut self, function: &Function) {
+        self.stack_size = function.stack_size;
+        self.function = function.name.clone();
+        self.labels.clear();
+        if !function.is_static {
+            self.code.push(format!(".globl {}", function.name));
+        }
+        self.code.push(head!(function.name, function.stack_size));
+
+        self.ret_buffer = function.ret_buffer;
+        self.alloca_bottom = function.alloca_bottom;
+        self.label_vlas = function.labels.clone();
+        if let Some(bottom) = function.alloca_bottom {
+            let address = self.get_ident_address(bottom);
+            self.code.push(format!("mov %rsp, {}(%rbp)", address));
+        }
+        let ret = &function.ret;
+        if let (Some(buffer), Class::Memory) = (function.ret_buffer, classify(ret)) {
+            let address = self.get_ident_address(buffer);
+            self.code.push(format!("mov %rdi, {}(%rbp)", address));
+        }
+
+        if let Some(area) = function.va_area {
+            let area = self.get_ident_address(area);
+            for (i, reg) in ARG_REGS_64.iter().enumerate() {
+                self.code
+                    .push(format!("mov {}, {}(%rbp)", reg, area + 8 * i as isize));
+            }
+            for i in 0..8 {
+                self.code
+                    .push(format!("movsd %xmm{}, {}(%rbp)", i, area + 48 + 16 * i));
+            }
+        }
+
+        // store the arguments to the locals of the parameters, those in registers first
+        // as copying those on the stack clobbers some of them.
+        let (places, regs, stack) = place_args(function.params.iter().map(|param| &param.ty), ret);
+        self.named_args = (regs, stack);
+        for (param, place) in function.params.iter().zip(&places) {
+            let address = self.get_ident_address(param.offset);
+            match *place {
+                Place::Reg(i) if param.ty.record().is_some() => {
+                    for (j, offset) in (0..param.ty.size()).step_by(8).enumerate() {
+                        let size = (param.ty.size() - offset).min(8);
+                        self.store_eightbyte(i + j, address + offset as isize, size);
+                    }
+                }
+                Place::Reg(i) => {
+                    let regs = match param.ty.size() {
+                        1 => ARG_REGS_8,
+                        2 => ARG_REGS_16,
+                        4 => ARG_REGS_32,
+                        _ => ARG_REGS_64,
+                    };
+                    self.code
+                        .push(format!("mov {}, {}(%rbp)", regs[i], address));
+                }
+                Place::Stack(_) => {}
+            }
+        }
+        for (param, place) in function.params.iter().zip(&places) {
+            // above the return address and the saved `%rbp`.
+            if let Place::Stack(offset) = *place {
+                let address = self.get_ident_address(param.offset);
+                self.code.push(format!("lea {}(%rbp), %rax", 16 + offset));
+                self.code.push(format!("lea {}(%rbp), %rdi", address));
+                self.copy(param.ty.size());
+            }
+        }
+
+        for stmt in &function.body {
+            self.statement(stmt);
+        }
+        self.code.push(tail!(function.name));
+    }
+
     fn count(&mut self) -> usize {
         self.count += 1;
         self.count
     }
 
     fn statement(&mut self, stmt: &Stmt) {
+        let start = stmt.loc().get_start();
+        self.code
+            .push(format!(".loc 1 {} {}", start.line, start.column + 1));
         match stmt {
             Stmt::Expr(stmt) => self.expression_statement(stmt),
             Stmt::Return(stmt) => self.return_statement(stmt),
             Stmt::Block(stmt) => self.block_statement(stmt),
-            Stmt::Empty(stmt) => self.empty_statement(stmt),
+            Stmt::Empty(_) => {}
             Stmt::If(stmt) => self.if_statement(stmt),
             Stmt::For(stmt) => self.for_statement(stmt),
             Stmt::While(stmt) => self.while_statement(stmt),
+            Stmt::Switch(stmt) => self.switch_statement(stmt),
+            Stmt::Case(stmt) => self.case_statement(stmt),
+            Stmt::Default(stmt) => self.default_statement(stmt),
+            Stmt::Break(stmt) => self.break_statement(stmt),
+            Stmt::Labeled(stmt) => self.labeled_statement(stmt),
+            Stmt::Goto(stmt) => self.goto_statement(stmt),
+            Stmt::Decl(stmt) => self.declaration(stmt),
+            Stmt::Asm(stmt) => self.asm_statement(stmt),
+        }
+    }
+
+    fn declaration(&mut self, stmt: &DeclStmt) {
+        stmt.inits.iter().for_each(|init| self.expression(init))
+    }
+
+    /// a user defined label may be jumped to before it is defined.
+    fn label(&mut self, name: &str) -> usize {
+        if let Some(c) = self.labels.get(name) {
+            *c
+        } else {
+            let c = self.count();
+            self.labels.insert(name.to_string(), c);
+            c
+        }
+    }
+
+    fn labeled_statement(&mut self, stmt: &LabeledStmt) {
+        let c = self.label(&stmt.label);
+        self.code.push(format!(".L.label.{}:", c));
+        self.statement(&stmt.body);
+    }
+
+    /// a `goto` out of the scope of variable-length arrays frees them.
+    fn goto_statement(&mut self, stmt: &GotoStmt) {
+        if let Some(&saved) = stmt.vlas.get(self.label_vlas[&stmt.label]) {
+            self.restore_bottom(saved);
+        }
+        let c = self.label(&stmt.label);
+        self.code.push(format!("jmp .L.label.{}", c));
+    }
+
+    fn asm_statement(&mut self, stmt: &AsmStmt) {
+        // evaluating an operand may use any register, so they are all kept in locals
+        // until the registers are loaded.
+        for operand in &stmt.operands {
+            self.expression(&operand.expr);
+            let slot = self.get_ident_address(operand.slot);
+            self.code.push(format!("mov %rax, {}(%rbp)", slot));
+        }
+        for reg in &stmt.saved {
+            self.code.push(format!("push {}", reg.name(8)));
+        }
+        for operand in &stmt.operands {
+            if operand.kind == (AsmKind::Output { is_read: false }) {
+                continue;
+            }
+            let slot = self.get_ident_address(operand.slot);
+            let reg = operand.reg.name(8);
+            self.code.push(format!("mov {}(%rbp), {}", slot, reg));
+            if operand.kind == (AsmKind::Output { is_read: true }) {
+                let value = operand.reg.name(4);
+                let load = match (operand.ty.size(), operand.ty.unsigned) {
+                    (1, true) => format!("movzbl ({}), {}", reg, value),
+                    (1, false) => format!("movsbl ({}), {}", reg, value),
+                    (2, true) => format!("movzwl ({}), {}", reg, value),
+                    (2, false) => format!("movswl ({}), {}", reg, value),
+                    (4, _) => format!("mov ({}), {}", reg, value),
+                    _ => format!("mov ({}), {}", reg, reg),
+                };
+                self.code.push(load);
+            }
+        }
+        self.code.push(stmt.text.clone());
+
+        let outputs: Vec<_> = stmt
+            .operands
+            .iter()
+            .filter(|operand| matches!(operand.kind, AsmKind::Output { .. }))
+            .collect();
+        for operand in &outputs {
+            self.code.push(format!("push {}", operand.reg.name(8)));
+        }
+        for operand in outputs.iter().rev() {
+            self.code.push("pop %rax".to_string());
+            let slot = self.get_ident_address(operand.slot);
+            self.code.push(format!("mov {}(%rbp), %rdi", slot));
+            self.store(&operand.ty);
+        }
+        for reg in stmt.saved.iter().rev() {
+            self.code.push(format!("pop {}", reg.name(8)));
+        }
+    }
+
+    fn switch_statement(&mut self, stmt: &SwitchStmt) {
+        let c = self.count();
+        self.expression(&stmt.test);
+        let fallback = if stmt.has_default {
+            format!(".L.default.{}", c)
+        } else {
+            format!(".L.end.{}", c)
+        };
+
+        // (value, index) sorted by value.
+        let mut cases: Vec<(i64, usize)> = stmt.cases.iter().copied().zip(0..).collect();
+        cases.sort_unstable();
+        if is_dense(&cases) {
+            self.jump_table(c, &cases, &fallback);
+        } else {
+            self.binary_search(c, &cases, &fallback);
+        }
+
+        self.breaks.push(c);
+        self.switches.push(c);
+        self.statement(&stmt.body);
+        self.switches.pop();
+        self.breaks.pop();
+        self.code.push(format!(".L.end.{}:", c));
+    }
+
+    /// jump through a table in `.rodata` indexed by `%rax - min`.
+    fn jump_table(&mut self, c: usize, cases: &[(i64, usize)], fallback: &str) {
+        let min = cases[0].0;
+        let range = cases[cases.len() - 1].0 - min + 1;
+        self.code.push(format!("mov ${}, %rdi", min));
+        self.code.push("sub %rdi, %rax".to_string());
+        self.code.push(format!("cmp ${}, %rax", range));
+        // unsigned compare, so values below `min` wrap around and are caught too.
+        self.code.push(format!("jae {}", fallback));
+        self.code.push(format!("lea .L.table.{}(%rip), %rdi", c));
+        self.code.push("movslq (%rdi,%rax,4), %rax".to_string());
+        self.code.push("add %rdi, %rax".to_string());
+        self.code.push("jmp *%rax".to_string());
+
+        self.rodata.push(".align 4".to_string());
+        self.rodata.push(format!(".L.table.{}:", c));
+        let mut cases = cases.iter().peekable();
+        for value in min..min + range {
+            let target = match cases.next_if(|(case, _)| *case == value) {
+                Some((_, index)) => format!(".L.case.{}.{}", c, index),
+                None => fallback.to_string(),
+            };
+            self.rodata
+                .push(format!(".long {} - .L.table.{}", target, c));
+        }
+    }
+
+    /// compare `%rax` against the sorted `cases`, bisecting while there are many of them.
+    fn binary_search(&mut self, c: usize, cases: &[(i64, usize)], fallback: &str) {
+        if cases.len() <= 4 {
+            for (value, index) in cases {
+                self.compare_case(*value);
+                self.code.push(format!("je .L.case.{}.{}", c, index));
+            }
+            self.code.push(format!("jmp {}", fallback));
+            return;
         }
+
+        let mid = cases.len() / 2;
+        let (value, index) = cases[mid];
+        let upper = self.count();
+        self.compare_case(value);
+        self.code.push(format!("je .L.case.{}.{}", c, index));
+        self.code.push(format!("jg .L.upper.{}", upper));
+        self.binary_search(c, &cases[..mid], fallback);
+        self.code.push(format!(".L.upper.{}:", upper));
+        self.binary_search(c, &cases[mid + 1..], fallback);
+    }
+
+    fn compare_case(&mut self, value: i64) {
+        if i32::try_from(value).is_ok() {
+            self.code.push(format!("cmp ${}, %rax", value));
+        } else {
+            self.code.push(format!("mov ${}, %rdi", value));
+            self.code.push("cmp %rdi, %rax".to_string());
+        }
+    }
+
+    fn case_statement(&mut self, stmt: &CaseStmt) {
+        let c = self.switches.last().unwrap();
+        self.code.push(format!(".L.case.{}.{}:", c, stmt.index));
+        self.statement(&stmt.body);
+    }
+
+    fn default_statement(&mut self, stmt: &DefaultStmt) {
+        let c = self.switches.last().unwrap();
+        self.code.push(format!(".L.default.{}:", c));
+        self.statement(&stmt.body);
+    }
+
+    fn break_statement(&mut self, stmt: &BreakStmt) {
+        if let Some(saved) = stmt.saved_bottom {
+            self.restore_bottom(saved);
+        }
+        let c = self.breaks.last().unwrap();
+        self.code.push(format!("jmp .L.end.{}", c));
     }
 
     fn while_statement(&mut self, stmt: &WhileStmt) {
         let c = self.count();
         self.code.push(format!(".L.begin.{}:", c));
         self.expression(&stmt.test);
-        self.code.push(format!("cmp $0, %rax"));
+        self.code.push("cmp $0, %rax".to_string());
         self.code.push(format!("je .L.end.{}", c));
+        self.breaks.push(c);
         self.statement(&stmt.body);
+        self.breaks.pop();
         self.code.push(format!("jmp .L.begin.{}", c));
         self.code.push(format!(".L.end.{}:", c));
     }
@@ -75,15 +517,17 @@ impl Context {
     fn for_statement(&mut self, stmt: &ForStmt) {
         let c = self.count();
         if let Some(init) = &stmt.init {
-            self.expression(init);
+            self.statement(init);
         }
         self.code.push(format!(".L.begin.{}:", c));
         if let Some(test) = &stmt.test {
             self.expression(test);
-            self.code.push(format!("cmp $0, %rax"));
+            self.code.push("cmp $0, %rax".to_string());
             self.code.push(format!("je .L.end.{}", c));
         }
+        self.breaks.push(c);
         self.statement(&stmt.body);
+        self.breaks.pop();
         if let Some(update) = &stmt.update {
             self.expression(update);
         }
@@ -94,7 +538,7 @@ impl Context {
     fn if_statement(&mut self, stmt: &IfStmt) {
         let c = self.count();
         self.expression(&stmt.test);
-        self.code.push(format!("cmp $0, %rax"));
+        self.code.push("cmp $0, %rax".to_string());
         self.code.push(format!("je .L.else.{}", c));
         self.statement(&stmt.consequent);
         self.code.push(format!("jmp .L.end.{}", c));
@@ -105,20 +549,51 @@ impl Context {
         self.code.push(format!(".L.end.{}:", c));
     }
 
-    fn empty_statement(&mut self, _stmt: &EmptyStmt) {
-        ()
+    /// a block declaring variable-length arrays gives their storage back to the stack when
+    /// it is left, down to the bottom saved by the first of them. `break` and `goto` free
+    /// the ones they jump out of the scope of themselves.
+    fn block_statement(&mut self, stmt: &BlockStmt) {
+        stmt.body.iter().for_each(|item| self.statement(item));
+        if let Some(saved) = stmt.saved_bottom {
+            self.restore_bottom(saved);
+        }
     }
 
-    fn block_statement(&mut self, stmt: &BlockStmt) {
-        stmt.body.iter().for_each(|item| self.statement(item))
+    /// move the bottom of the stack allocations back to the one saved in the local `saved`,
+    /// freeing everything allocated since.
+    fn restore_bottom(&mut self, saved: Offset) {
+        let saved = self.get_ident_address(saved);
+        let bottom = self.get_ident_address(self.alloca_bottom.unwrap());
+        self.code.push(format!("mov {}(%rbp), %rax", saved));
+        self.code.push(format!("mov %rax, {}(%rbp)", bottom));
+        self.code.push("mov %rax, %rsp".to_string());
     }
 
+    /// a record is returned in `%rax` and `%rdx`, or copied to the buffer of the caller.
     fn return_statement(&mut self, stmt: &ReturnStmt) {
         if let Some(expr) = &stmt.argument {
             self.expression(expr);
+            let ty = expr.ty();
+            if ty.record().is_some() {
+                match classify(ty) {
+                    Class::Integer(n) => {
+                        self.code.push("mov %rax, %rcx".to_string());
+                        for (i, reg) in ["%rax", "%rdx"].into_iter().enumerate().take(n) {
+                            let size = (ty.size() - 8 * i).min(8);
+                            self.load_eightbyte(reg, 8 * i, size);
+                        }
+                    }
+                    Class::Memory => {
+                        let buffer = self.get_ident_address(self.ret_buffer.unwrap());
+                        self.code.push(format!("mov {}(%rbp), %rdi", buffer));
+                        self.copy(ty.size());
+                        self.code.push("mov %rdi, %rax".to_string());
+                    }
+                }
+            }
         }
 
-        self.code.push(format!("jmp .L.return"));
+        self.code.push(format!("jmp .L.return.{}", self.function));
     }
 
     fn expression_statement(&mut self, stmt: &ExprStmt) {
@@ -134,141 +609,601 @@ impl Context {
             Expr::Ident(ident) => self.ident_expression(ident),
             Expr::Deref(deref) => self.deref_expression(deref),
             Expr::Addr(addr) => self.addr_expression(addr),
+            Expr::Cast(cast) => self.cast_expression(cast),
+            Expr::Member(member) => self.member_expression(member),
+            Expr::MemZero(expr) => self.mem_zero(expr),
+            Expr::CompoundLit(expr) => {
+                self.compound_literal(expr);
+                self.load(&expr.ty);
+            }
+            Expr::Call(expr) => self.call_expression(expr),
+            Expr::VaStart(expr) => self.va_start(expr),
+            Expr::VaArg(expr) => self.va_arg(expr),
+            Expr::Alloca(expr) => self.alloca(expr),
+        }
+    }
+
+    fn va_start(&mut self, expr: &VaStartExpr) {
+        let (regs, stack) = self.named_args;
+        let gp_offset = 8 * regs;
+        // the named parameters passed on the stack come before the variadic ones.
+        let overflow = 16 + stack;
+        let area = self.get_ident_address(expr.area);
+        self.expression(&expr.ap);
+        self.code.push(format!("movl ${}, (%rax)", gp_offset));
+        self.code.push("movl $48, 4(%rax)".to_string());
+        self.code.push(format!("lea {}(%rbp), %rdx", overflow));
+        self.code.push("mov %rdx, 8(%rax)".to_string());
+        self.code.push(format!("lea {}(%rbp), %rdx", area));
+        self.code.push("mov %rdx, 16(%rax)".to_string());
+    }
+
+    /// move the values pushed so far below the new allocation, which is 16-byte aligned
+    /// to keep calls aligned, and leave its address in `%rax`.
+    fn alloca(&mut self, expr: &AllocaExpr) {
+        self.expression(&expr.size);
+        let bottom = self.get_ident_address(expr.bottom);
+        let count = self.count();
+        self.code.push("mov %rax, %rdi".to_string());
+        self.code.push("add $15, %rdi".to_string());
+        self.code.push("and $-16, %rdi".to_string());
+        self.code.push(format!("mov {}(%rbp), %rcx", bottom));
+        self.code.push("sub %rsp, %rcx".to_string());
+        self.code.push("mov %rsp, %rax".to_string());
+        self.code.push("sub %rdi, %rsp".to_string());
+        self.code.push("mov %rsp, %rdx".to_string());
+        self.code.push(format!(".L.alloca.{}:", count));
+        self.code.push("cmp $0, %rcx".to_string());
+        self.code.push(format!("je .L.alloca.end.{}", count));
+        self.code.push("mov (%rax), %r8".to_string());
+        self.code.push("mov %r8, (%rdx)".to_string());
+        self.code.push("add $8, %rax".to_string());
+        self.code.push("add $8, %rdx".to_string());
+        self.code.push("sub $8, %rcx".to_string());
+        self.code.push(format!("jmp .L.alloca.{}", count));
+        self.code.push(format!(".L.alloca.end.{}:", count));
+        self.code.push(format!("mov {}(%rbp), %rax", bottom));
+        self.code.push("sub %rdi, %rax".to_string());
+        self.code.push(format!("mov %rax, {}(%rbp)", bottom));
+    }
+
+    /// take the address of the next argument from the register save area while it has
+    /// some left, and from the stack after that.
+    fn va_arg(&mut self, expr: &VaArgExpr) {
+        let c = self.count();
+        self.expression(&expr.ap);
+        self.code.push("mov %rax, %rdx".to_string());
+        self.code.push("mov (%rdx), %ecx".to_string());
+        self.code.push("cmp $48, %ecx".to_string());
+        self.code.push(format!("jae .L.va.stack.{}", c));
+        self.code.push("mov 16(%rdx), %rax".to_string());
+        self.code.push("add %rcx, %rax".to_string());
+        self.code.push("add $8, %ecx".to_string());
+        self.code.push("mov %ecx, (%rdx)".to_string());
+        self.code.push(format!("jmp .L.va.end.{}", c));
+        self.code.push(format!(".L.va.stack.{}:", c));
+        self.code.push("mov 8(%rdx), %rax".to_string());
+        self.code.push("lea 8(%rax), %rcx".to_string());
+        self.code.push("mov %rcx, 8(%rdx)".to_string());
+        self.code.push(format!(".L.va.end.{}:", c));
+        self.load(&expr.ty);
+    }
+
+    /// the arguments are pushed from the last one: first those passed on the stack, so
+    /// that they are left in order, then those passed in registers, which are popped to
+    /// their registers.
+    fn call_expression(&mut self, expr: &CallExpr) {
+        let (places, regs, stack) = place_args(expr.args.iter().map(|arg| arg.ty()), &expr.ty);
+        // the stack is 16-byte aligned at the call, once the stack arguments are pushed.
+        let padding = (self.depth + stack / 8) % 2;
+        if padding == 1 {
+            self.code.push("sub $8, %rsp".to_string());
+            self.depth += 1;
+        }
+        for (arg, _) in expr
+            .args
+            .iter()
+            .zip(&places)
+            .rev()
+            .filter(|(_, place)| matches!(place, Place::Stack(_)))
+        {
+            if arg.ty().record().is_some() {
+                let size = align(arg.ty().size(), 8);
+                self.code.push(format!("sub ${}, %rsp", size));
+                self.depth += size / 8;
+                self.expression(arg);
+                self.code.push("mov %rsp, %rdi".to_string());
+                self.copy(arg.ty().size());
+            } else {
+                self.expression(arg);
+                self.push();
+            }
+        }
+        for (arg, _) in expr
+            .args
+            .iter()
+            .zip(&places)
+            .rev()
+            .filter(|(_, place)| matches!(place, Place::Reg(_)))
+        {
+            self.expression(arg);
+            if arg.ty().record().is_some() {
+                // each eightbyte, the last one first.
+                self.code.push("mov %rax, %rcx".to_string());
+                for offset in (0..arg.ty().size()).step_by(8).rev() {
+                    let size = (arg.ty().size() - offset).min(8);
+                    self.load_eightbyte("%rax", offset, size);
+                    self.push();
+                }
+            } else {
+                self.push();
+            }
+        }
+        let buffer = expr.ret_buffer.map(|buffer| self.get_ident_address(buffer));
+        if let (Some(buffer), Class::Memory) = (buffer, classify(&expr.ty)) {
+            self.code.push(format!("lea {}(%rbp), %rax", buffer));
+            self.push();
+        }
+        let direct = match &*expr.callee {
+            Expr::Ident(IdentExpr {
+                storage: Storage::Global(name),
+                ..
+            }) => Some(name),
+            _ => None,
+        };
+        if direct.is_none() {
+            self.expression(&expr.callee);
+            self.code.push("mov %rax, %r11".to_string());
+        }
+        for reg in ARG_REGS_64.iter().take(regs) {
+            self.pop(reg);
+        }
+        // the number of vector registers used by a variadic callee.
+        self.code.push("mov $0, %eax".to_string());
+        match direct {
+            Some(name) => self.code.push(format!("call {}", name)),
+            None => self.code.push("call *%r11".to_string()),
+        }
+        if stack + 8 * padding > 0 {
+            self.code
+                .push(format!("add ${}, %rsp", stack + 8 * padding));
+            self.depth -= stack / 8 + padding;
+        }
+
+        // a returned record is the buffer, filled by the callee if it is in memory.
+        if let Some(buffer) = buffer {
+            if let Class::Integer(n) = classify(&expr.ty) {
+                for (i, reg) in ["%rax", "%rdx"].into_iter().enumerate().take(n) {
+                    let size = (expr.ty.size() - 8 * i).min(8);
+                    self.store_bytes(reg, buffer + 8 * i as isize, size);
+                }
+            }
+            self.code.push(format!("lea {}(%rbp), %rax", buffer));
+            return;
+        }
+
+        // the callee leaves the bits above a narrow return value undefined.
+        if expr.ty.is_void() {
+            return;
+        }
+        match (expr.ty.size(), expr.ty.unsigned) {
+            (1, true) => self.code.push("movzbl %al, %eax".to_string()),
+            (1, false) => self.code.push("movsbl %al, %eax".to_string()),
+            (2, true) => self.code.push("movzwl %ax, %eax".to_string()),
+            (2, false) => self.code.push("movswl %ax, %eax".to_string()),
+            (4, true) => self.code.push("mov %eax, %eax".to_string()),
+            (4, false) => self.code.push("movslq %eax, %rax".to_string()),
+            _ => {}
+        }
+    }
+
+    /// load the `size` bytes at `offset` from the address in `%rcx` to the register `reg`.
+    fn load_eightbyte(&mut self, reg: &str, offset: usize, size: usize) {
+        if size == 8 {
+            self.code.push(format!("mov {}(%rcx), {}", offset, reg));
+            return;
+        }
+        // byte by byte from the last one, so that nothing past the record is read.
+        self.code.push(format!("mov $0, {}", reg));
+        for i in (0..size).rev() {
+            self.code.push(format!("shl $8, {}", reg));
+            self.code
+                .push(format!("mov {}(%rcx), {}", offset + i, byte_reg(reg)));
+        }
+    }
+
+    /// store the argument register `i` to the `size` bytes at `address` in the frame.
+    fn store_eightbyte(&mut self, i: usize, address: isize, size: usize) {
+        self.code.push(format!("mov {}, %rax", ARG_REGS_64[i]));
+        self.store_bytes("%rax", address, size);
+    }
+
+    /// store the low `size` bytes of `reg` to `address` in the frame, clobbering `reg`.
+    fn store_bytes(&mut self, reg: &str, address: isize, size: usize) {
+        if size == 8 {
+            self.code.push(format!("mov {}, {}(%rbp)", reg, address));
+            return;
+        }
+        for i in 0..size {
+            self.code.push(format!(
+                "mov {}, {}(%rbp)",
+                byte_reg(reg),
+                address + i as isize
+            ));
+            self.code.push(format!("shr $8, {}", reg));
+        }
+    }
+
+    fn member_expression(&mut self, expr: &MemberExpr) {
+        self.member_address(expr);
+        match expr.bit_field {
+            Some(bit_field) => self.load_bit_field(&expr.ty, bit_field),
+            None => self.load(&expr.ty),
+        }
+    }
+
+    /// load the bit-field in the storage unit at `%rax`, reading only the bytes it takes,
+    /// which are nine at most when a packed bit-field does not start at a byte.
+    fn load_bit_field(&mut self, ty: &Type, bit_field: BitField) {
+        let BitField { offset, width } = bit_field;
+        let (first, shift) = (offset / 8, offset % 8);
+        let size = (shift + width).div_ceil(8);
+        self.code.push("mov %rax, %rcx".to_string());
+        self.load_eightbyte("%rax", first, size.min(8));
+        if size > 8 {
+            self.load_eightbyte("%rdx", first + 8, 1);
+            self.code.push(format!("shrd ${}, %rdx, %rax", shift));
+            self.extract_bit_field(ty, BitField { offset: 0, width });
+        } else {
+            self.extract_bit_field(
+                ty,
+                BitField {
+                    offset: shift,
+                    width,
+                },
+            );
+        }
+    }
+
+    /// the value of the bit-field in `%rax`, extended by the signedness of its type.
+    fn extract_bit_field(&mut self, ty: &Type, bit_field: BitField) {
+        let BitField { offset, width } = bit_field;
+        self.code
+            .push(format!("shl ${}, %rax", 64 - width - offset));
+        if ty.unsigned {
+            self.code.push(format!("shr ${}, %rax", 64 - width));
+        } else {
+            self.code.push(format!("sar ${}, %rax", 64 - width));
+        }
+    }
+
+    /// store `%rax` to the bit-field in the storage unit at `%rdi`, keeping the other bits
+    /// of the bytes it takes and touching no other byte, and leave the new value of the
+    /// bit-field in `%rax`.
+    fn store_bit_field(&mut self, ty: &Type, bit_field: BitField) {
+        let BitField { offset, width } = bit_field;
+        let (first, shift) = (offset / 8, offset % 8);
+        let size = (shift + width).div_ceil(8);
+        let mask = (u64::MAX >> (64 - width)) << shift;
+        self.code.push("mov %rax, %r8".to_string());
+        self.code.push("mov %rdi, %rcx".to_string());
+        self.load_eightbyte("%rdx", first, size.min(8));
+        self.code.push(format!("mov ${}, %rsi", mask as i64));
+        self.code.push(format!("shl ${}, %rax", shift));
+        self.code.push("and %rsi, %rax".to_string());
+        self.code.push("not %rsi".to_string());
+        self.code.push("and %rsi, %rdx".to_string());
+        self.code.push("or %rax, %rdx".to_string());
+        for i in 0..size.min(8) {
+            self.code.push(format!("mov %dl, {}(%rdi)", first + i));
+            self.code.push("shr $8, %rdx".to_string());
+        }
+        if size > 8 {
+            // the bits past the first eight bytes go to the low bits of the ninth.
+            let high = (1 << (shift + width - 64)) - 1;
+            self.code.push("mov %r8, %rax".to_string());
+            self.code.push(format!("shr ${}, %rax", 64 - shift));
+            self.code.push(format!("and ${}, %eax", high));
+            self.code.push(format!("movzbl {}(%rdi), %edx", first + 8));
+            self.code.push(format!("and ${}, %edx", !high & 0xff));
+            self.code.push("or %eax, %edx".to_string());
+            self.code.push(format!("mov %dl, {}(%rdi)", first + 8));
+        }
+        self.code.push("mov %r8, %rax".to_string());
+        self.extract_bit_field(ty, BitField { offset: 0, width });
+    }
+
+    fn member_address(&mut self, expr: &MemberExpr) {
+        self.address(&expr.base);
+        self.code.push(format!("add ${}, %rax", expr.offset));
+    }
+
+    fn cast_expression(&mut self, expr: &CastExpr) {
+        self.expression(&expr.argument);
+        self.cast(expr.argument.ty(), &expr.ty);
+    }
+
+    /// only the low `size` bytes of `%rax` hold a value, where values narrower
+    /// than `int` are kept extended to 32 bits by the signedness of their type.
+    fn cast(&mut self, from: &Type, to: &Type) {
+        if to.is_void() {
+            return;
+        }
+        if to.kind == TypeKind::Bool {
+            // any nonzero value, including a pointer, becomes 1.
+            if from.size() == 8 {
+                self.code.push("cmp $0, %rax".to_string());
+            } else {
+                self.code.push("cmp $0, %eax".to_string());
+            }
+            self.code.push("setne %al".to_string());
+            self.code.push("movzbl %al, %eax".to_string());
+            return;
+        }
+        match to.size() {
+            1 if to.unsigned => self.code.push("movzbl %al, %eax".to_string()),
+            1 => self.code.push("movsbl %al, %eax".to_string()),
+            2 if to.unsigned => self.code.push("movzwl %ax, %eax".to_string()),
+            2 => self.code.push("movswl %ax, %eax".to_string()),
+            // the tests of conditions compare the whole of `%rax`.
+            4 if from.size() == 8 && to.unsigned => self.code.push("mov %eax, %eax".to_string()),
+            4 if from.size() == 8 => self.code.push("movslq %eax, %rax".to_string()),
+            8 if from.is_integer() && from.size() < 8 => {
+                if from.unsigned {
+                    self.code.push("mov %eax, %eax".to_string())
+                } else {
+                    self.code.push("movslq %eax, %rax".to_string())
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// load the value of type `ty` at the address in `%rax`.
+    fn load(&mut self, ty: &Type) {
+        if ty.is_array() || ty.record().is_some() || ty.func_type().is_some() {
+            // the address of an array, a struct or a function is its value.
+            return;
+        }
+        if ty.is_void() {
+            return;
+        }
+        let load = match (ty.size(), ty.unsigned) {
+            (1, true) => "movzbl (%rax), %eax",
+            (1, false) => "movsbl (%rax), %eax",
+            (2, true) => "movzwl (%rax), %eax",
+            (2, false) => "movswl (%rax), %eax",
+            (4, _) => "mov (%rax), %eax",
+            _ => "mov (%rax), %rax",
+        };
+        self.code.push(load.to_string());
+    }
+
+    /// store `%rax` to the address in `%rdi`.
+    fn store(&mut self, ty: &Type) {
+        if ty.record().is_some() {
+            self.copy(ty.size());
+            return;
+        }
+        let store = match ty.size() {
+            1 => "mov %al, (%rdi)",
+            2 => "mov %ax, (%rdi)",
+            4 => "mov %eax, (%rdi)",
+            _ => "mov %rax, (%rdi)",
+        };
+        self.code.push(store.to_string());
+    }
+
+    /// copy `size` bytes from the address in `%rax` to the address in `%rdi`.
+    fn copy(&mut self, size: usize) {
+        let mut offset = 0;
+        for (width, reg) in [(8, "%r8"), (4, "%r8d"), (2, "%r8w"), (1, "%r8b")] {
+            while size - offset >= width {
+                self.code.push(format!("mov {}(%rax), {}", offset, reg));
+                self.code.push(format!("mov {}, {}(%rdi)", reg, offset));
+                offset += width;
+            }
         }
     }
 
     fn deref_expression(&mut self, deref: &DerefExpr) {
         self.expression(&deref.argument);
-        self.code.push(format!("mov (%rax), %rax"));
+        self.load(&deref.ty);
+    }
+
+    /// initialize the unnamed object of a compound literal, leaving its address in `%rax`.
+    fn compound_literal(&mut self, expr: &CompoundLitExpr) {
+        for init in &expr.inits {
+            self.expression(init);
+        }
+        self.ident_address(&expr.var);
+    }
+
+    fn mem_zero(&mut self, expr: &MemZeroExpr) {
+        self.ident_address(&expr.target);
+        self.code.push("mov %rax, %rdi".to_string());
+        self.code.push(format!("mov ${}, %rcx", expr.ty.size()));
+        self.code.push("mov $0, %al".to_string());
+        self.code.push("rep stosb".to_string());
     }
 
     fn addr_expression(&mut self, addr: &AddrExpr) {
-        self.expression(&addr.argument);
-        self.code.pop();
+        self.address(&addr.argument);
+    }
+
+    /// compute the address of the lvalue `expr` to `%rax`.
+    fn address(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Ident(ident) => self.ident_address(ident),
+            Expr::Deref(deref) => self.expression(&deref.argument),
+            Expr::Member(member) => self.member_address(member),
+            Expr::CompoundLit(expr) => self.compound_literal(expr),
+            // a returned record lives in a local of the caller.
+            Expr::Call(expr) => self.call_expression(expr),
+            _ => unreachable!(),
+        }
+    }
+
+    /// the distance between neighbouring elements a pointer of type `ty` points to, as an
+    /// operand: a local holds it for a variable-length array.
+    fn element_size(&mut self, ty: &Type) -> String {
+        match ty.base() {
+            Some(base) => match base.vla_size() {
+                Some(size) => format!("{}(%rbp)", self.get_ident_address(size)),
+                None => format!("${}", base.size()),
+            },
+            None => "$1".to_string(),
+        }
+    }
+
+    fn get_ident_address(&mut self, offset: Offset) -> isize {
+        offset as isize - self.stack_size as isize
     }
 
-    fn get_ident_address(&mut self, expr: &IdentExpr) -> isize {
-        -1 * ((self.stack_size - expr.offset * 8) as isize)
+    fn ident_address(&mut self, expr: &IdentExpr) {
+        match &expr.storage {
+            Storage::Local(offset) => {
+                let address = self.get_ident_address(*offset);
+                // lea: load effective address
+                // (%rbp) + address -> %rax
+                self.code.push(format!("lea {}(%rbp), %rax", address));
+            }
+            Storage::Global(name) => self.code.push(format!("lea {}(%rip), %rax", name)),
+        }
+        // a variable-length array holds the address of its elements.
+        if expr.ty.vla_size().is_some() {
+            self.code.push("mov (%rax), %rax".to_string());
+        }
     }
 
     fn ident_expression(&mut self, expr: &IdentExpr) {
-        let address = self.get_ident_address(expr);
-        // lea: load effective address
-        // (%rbp) + address -> %rax
-        self.code.push(format!("lea {}(%rbp), %rax", address));
-        self.code.push(format!("mov (%rax), %rax"));
+        self.ident_address(expr);
+        self.load(&expr.ty);
     }
 
     fn assign_expression(&mut self, expr: &AssignExpr) {
         // left
         match &*expr.left {
-            LeftVal::Ident(ident) => {
-                let address = self.get_ident_address(ident);
-                self.code.push(format!("lea {}(%rbp), %rax", address));
-            }
+            LeftVal::Ident(ident) => self.ident_address(ident),
+            LeftVal::Member(member) => self.member_address(member),
             LeftVal::Deref(deref) => self.expression(&deref.argument),
         }
 
         // --
-        self.code.push(push!());
+        self.push();
         self.expression(&expr.right);
-        self.code.push(pop!("%rdi"));
-        // move the value of (%rdi) to %rax
-        self.code.push(format!("mov %rax, (%rdi)"));
+        self.pop("%rdi");
+        // move the value of %rax to (%rdi)
+        match &*expr.left {
+            LeftVal::Member(MemberExpr {
+                bit_field: Some(bit_field),
+                ..
+            }) => self.store_bit_field(&expr.ty, *bit_field),
+            _ => self.store(&expr.ty),
+        }
     }
 
     fn binary_expression(&mut self, expr: &BinaryExpr) {
         self.expression(&expr.right);
-        self.code.push(push!());
+        self.push();
         self.expression(&expr.left);
-        self.code.push(pop!("%rdi"));
+        self.pop("%rdi");
+
+        // operands of the same type, computed in 32 or 64 bits.
+        let operand = expr.left.ty();
+        let (ax, di) = if operand.size() == 8 {
+            ("%rax", "%rdi")
+        } else {
+            ("%eax", "%edi")
+        };
+        let unsigned = operand.unsigned;
 
         use BinaryOp::*;
         match expr.op {
             Add => {
-                self.code.push(format!("add %rdi, %rax"));
+                self.code.push(format!("add {}, {}", di, ax));
             }
             AddrAdd(ref pos) => {
                 use crate::ast::BinaryAddrPos::*;
                 match pos {
                     Left => {
-                        self.code.push(format!("imul $8, %rdi"));
-                        self.code.push(format!("add %rdi, %rax"));
+                        let size = self.element_size(&expr.ty);
+                        self.code.push(format!("imul {}, %rdi", size));
+                        self.code.push("add %rdi, %rax".to_string());
                     }
                     Right => {
-                        self.code.push(format!("imul $8, %rax"));
-                        self.code.push(format!("add %rdi, %rax"));
+                        let size = self.element_size(&expr.ty);
+                        self.code.push(format!("imul {}, %rax", size));
+                        self.code.push("add %rdi, %rax".to_string());
                     }
                     _ => unreachable!(),
                 }
             }
             Sub => {
-                self.code.push(format!("sub %rdi, %rax"));
+                self.code.push(format!("sub {}, {}", di, ax));
             }
             AddrSub(ref pos) => {
                 use crate::ast::BinaryAddrPos::*;
                 match pos {
                     Left => {
-                        self.code.push(format!("imul $8, %rdi"));
-                        self.code.push(format!("sub %rdi, %rax"));
+                        let size = self.element_size(&expr.ty);
+                        self.code.push(format!("imul {}, %rdi", size));
+                        self.code.push("sub %rdi, %rax".to_string());
                     }
                     Both => {
-                        self.code.push(format!("sub %rdi, %rax"));
+                        self.code.push("sub %rdi, %rax".to_string());
                         // remove offset
-                        self.code.push(format!("mov $8, %rdi"));
-                        self.code.push(format!("cqo"));
-                        self.code.push(format!("idiv %rdi"));
+                        let size = self.element_size(expr.left.ty());
+                        self.code.push(format!("mov {}, %rdi", size));
+                        self.code.push("cqo".to_string());
+                        self.code.push("idiv %rdi".to_string());
                     }
                     _ => unreachable!(),
                 }
             }
             Mul => {
-                self.code.push(format!("imul %rdi, %rax"));
+                self.code.push(format!("imul {}, {}", di, ax));
             }
             Div => {
-                self.code.push(format!("cqo"));
-                self.code.push(format!("idiv %rdi"));
-            }
-            Equal => {
-                self.code.push(format!("cmp %rdi, %rax"));
-                self.code.push(format!("sete %al"));
-                self.code.push(format!("movzb %al, %rax"));
-            }
-            NotEqual => {
-                self.code.push(format!("cmp %rdi, %rax"));
-                self.code.push(format!("setne %al"));
-                self.code.push(format!("movzb %al, %rax"));
-            }
-            Less => {
-                self.code.push(format!("cmp %rdi, %rax"));
-                self.code.push(format!("setl %al"));
-                self.code.push(format!("movzb %al, %rax"));
-            }
-            LessEqual => {
-                self.code.push(format!("cmp %rdi, %rax"));
-                self.code.push(format!("setle %al"));
-                self.code.push(format!("movzb %al, %rax"));
-            }
-            Great => {
-                self.code.push(format!("cmp %rdi, %rax"));
-                self.code.push(format!("setg %al"));
-                self.code.push(format!("movzb %al, %rax"));
+                if unsigned {
+                    self.code.push("mov $0, %edx".to_string());
+                    self.code.push(format!("div {}", di));
+                } else if operand.size() == 8 {
+                    self.code.push("cqo".to_string());
+                    self.code.push(format!("idiv {}", di));
+                } else {
+                    self.code.push("cdq".to_string());
+                    self.code.push(format!("idiv {}", di));
+                }
             }
-            GreatEqual => {
-                self.code.push(format!("cmp %rdi, %rax"));
-                self.code.push(format!("setge %al"));
-                self.code.push(format!("movzb %al, %rax"));
+            Equal | NotEqual | Less | LessEqual | Great | GreatEqual => {
+                let set = match (&expr.op, unsigned) {
+                    (Equal, _) => "sete",
+                    (NotEqual, _) => "setne",
+                    (Less, false) => "setl",
+                    (Less, true) => "setb",
+                    (LessEqual, false) => "setle",
+                    (LessEqual, true) => "setbe",
+                    (Great, false) => "setg",
+                    (Great, true) => "seta",
+                    (GreatEqual, false) => "setge",
+                    (GreatEqual, true) => "setae",
+                    _ => unreachable!(),
+                };
+                self.code.push(format!("cmp {}, {}", di, ax));
+                self.code.push(format!("{} %al", set));
+                self.code.push("movzb %al, %rax".to_string());
             }
         }
     }
 
     fn literal(&mut self, lit: &Lit) {
         match lit {
-            Lit::Int32(lit) => self.int32_literal(lit),
+            Lit::Int(lit) => self.int_literal(lit),
         }
     }
 
-    fn int32_literal(&mut self, lit: &Int32Lit) {
+    fn int_literal(&mut self, lit: &IntLit) {
         self.code.push(format!("mov ${}, %rax", lit.num));
     }
 
@@ -278,9 +1213,33 @@ impl Context {
             Plus => self.expression(&expr.argument),
             Minus => {
                 self.expression(&expr.argument);
-                self.code.push(format!("neg %rax"));
+                if expr.ty.size() == 8 {
+                    self.code.push("neg %rax".to_string());
+                } else {
+                    self.code.push("neg %eax".to_string());
+                }
             }
             _ => unreachable!(),
         }
     }
 }
+
+/// a jump table pays off once there are enough cases and few holes between them.
+fn is_dense(cases: &[(i64, usize)]) -> bool {
+    match (cases.first(), cases.last()) {
+        (Some((min, _)), Some((max, _))) if cases.len() >= 4 => {
+            let range = max.abs_diff(*min) + 1;
+            range <= 1024 && range <= 3 * cases.len() as u64
+        }
+        _ => false,
+    }
+}
+
+/// the lowest byte of `%rax`, `%rcx` or `%rdx`.
+fn byte_reg(reg: &str) -> &'static str {
+    match reg {
+        "%rax" => "%al",
+        "%rcx" => "%cl",
+        _ => "%dl",
+    }
+}
@@ -0,0 +1,70 @@
+use crate::{
+    ast::{CastExpr, Expr, Lit},
+    error::{SError, SWarning, SyntaxError, Warning},
+    state::{SResult, State},
+    ty::{Type, TypeKind},
+};
+
+/// wrap `expr` in a conversion to `ty`, unless it already has that type.
+pub(crate) fn cast(expr: Expr, ty: &Type) -> Expr {
+    if expr.ty().unqualified() == ty.unqualified() {
+        expr
+    } else {
+        Expr::Cast(CastExpr {
+            loc: expr.loc(),
+            argument: Box::new(expr),
+            ty: ty.clone(),
+        })
+    }
+}
+
+/// an array used as a value is converted to a pointer to its first element.
+pub(crate) fn decay(expr: Expr) -> Expr {
+    let ty = expr.ty().decay();
+    cast(expr, &ty)
+}
+
+/// convert both operands of an arithmetic operator to their common type.
+pub(crate) fn usual_arith(left: Expr, right: Expr) -> (Expr, Expr, Type) {
+    let ty = Type::common(left.ty(), right.ty());
+    (cast(left, &ty), cast(right, &ty), ty)
+}
+
+fn is_null_pointer(expr: &Expr) -> bool {
+    matches!(expr, Expr::Literal(Lit::Int(lit)) if lit.num.parse() == Ok(0))
+}
+
+impl State {
+    /// convert `expr` to `ty` as if by assignment, warning when a pointer
+    /// and an integer are mixed without an explicit cast.
+    /// A pointer may gain qualifiers on what it points to, but never lose them,
+    /// and a `void` pointer converts to and from any other object pointer.
+    pub(crate) fn convert(&mut self, expr: Expr, ty: &Type) -> SResult<Expr> {
+        let expr = decay(expr);
+        let pos = expr.loc().get_start().clone();
+        if expr.ty().is_void() {
+            return Err(SError::new(pos, SyntaxError::IncompatibleTypes));
+        }
+        let warning = match (expr.ty().base(), ty.base()) {
+            // a pointer tested for null is fine.
+            (Some(_), None) if ty.kind == TypeKind::Bool => None,
+            (Some(_), None) => Some(Warning::IntFromPointer),
+            (None, Some(_)) if !is_null_pointer(&expr) => Some(Warning::PointerFromInt),
+            (Some(from), Some(to)) if !to.qual.contains(from.qual) => {
+                return Err(SError::new(pos, SyntaxError::DiscardsQualifiers));
+            }
+            (Some(from), Some(to))
+                if !(from.unqualified().is_compatible(&to.unqualified())
+                    || from.is_void() && to.func_type().is_none()
+                    || to.is_void() && from.func_type().is_none()) =>
+            {
+                Some(Warning::IncompatiblePointer)
+            }
+            _ => None,
+        };
+        if let Some(warning) = warning {
+            self.warnings.push(SWarning::new(pos, warning));
+        }
+        Ok(cast(expr, ty))
+    }
+}
@@ -0,0 +1,1102 @@
+use crate::{
+    ast::{
+        AllocaExpr, AssignExpr, BinaryExpr, BinaryOp, DeclStmt, Expr, Function, IdentExpr, IntLit,
+        LeftVal, Lit, Stmt,
+    },
+    conversion::cast,
+    error::{SError, SWarning, SyntaxError, Warning},
+    layout::{MemberDecl, RecordRef},
+    object::{Global, Offset, Storage, Symbol},
+    state::{SResult, State},
+    token::TokenType,
+    ty::{Qualifiers, Type, TypeKind},
+    utils::{align, Loc, Pos},
+};
+
+/// storage-class specifiers, alignment specifiers and attributes, which are part of
+/// a declaration but not of its type.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct VarAttr {
+    pub is_typedef: bool,
+    pub is_static: bool,
+    pub is_extern: bool,
+    /// the alignment given by `_Alignas` or the `aligned` attribute, which only raises
+    /// that of the type.
+    pub align: Option<usize>,
+    /// the `packed` attribute, which only has an effect on records and their members.
+    pub is_packed: bool,
+}
+
+impl VarAttr {
+    /// the alignment of an object of type `ty` so declared.
+    fn align_of(&self, ty: &Type) -> usize {
+        ty.align().max(self.align.unwrap_or(1))
+    }
+}
+
+impl State {
+    /// whether `tt` starts a declaration, so that `T * x;` declares `x` when `T` names a type.
+    pub(crate) fn is_type_name(&self, tt: &TokenType) -> bool {
+        use TokenType::*;
+        match tt {
+            Void | Bool | Char | Short | Int | Long | Signed | Unsigned | Struct | Union | Enum
+            | Typedef | Static | Extern | Const | Volatile | Restrict | Alignas | Attribute
+            | StaticAssert => true,
+            Name(name) => self.locals.find_typedef(name).is_some(),
+            _ => false,
+        }
+    }
+
+    /// whether the current `(` opens a type name, as in `sizeof(int)`.
+    pub(crate) fn is_paren_type_name(&mut self) -> SResult<bool> {
+        if matches!(self.cur_token().get_type(), TokenType::ParenL) {
+            let next = self.peek_token()?;
+            Ok(self.is_type_name(next.get_type()))
+        } else {
+            Ok(false)
+        }
+    }
+
+    pub(crate) fn parse_paren_type_name(&mut self) -> SResult<Type> {
+        self.expect(&TokenType::ParenL)?;
+        let ty = self.parse_type_name()?;
+        self.expect(&TokenType::ParenR)?;
+        Ok(ty)
+    }
+
+    /// declaration = declspec (declarator ("=" expr)? ("," declarator ("=" expr)?)*)? ";"
+    ///             | declspec declarator "{" stmt* "}"
+    ///             | static-assert
+    pub(crate) fn parse_declaration(&mut self) -> SResult<DeclStmt> {
+        let start = self.cur_token_start();
+        if matches!(self.cur_token().get_type(), TokenType::StaticAssert) {
+            self.parse_static_assert()?;
+            return Ok(DeclStmt {
+                loc: self.finish_loc(start),
+                inits: vec![],
+            });
+        }
+        let mut attr = VarAttr::default();
+        let base = self.parse_declspec(Some(&mut attr))?;
+        let mut inits = vec![];
+        let mut first = true;
+        while !self.eat(&TokenType::Semi)? {
+            let is_first = std::mem::replace(&mut first, false);
+            if !is_first {
+                self.expect(&TokenType::Comma)?;
+            }
+
+            let name_start = self.cur_token_start();
+            let (name, ty) = self.parse_declarator(base.clone())?;
+            let mut attr = attr.clone();
+            self.parse_attributes(&mut attr)?;
+            // the sizes of variable-length arrays are computed where they are declared.
+            let sizes = std::mem::take(&mut self.vla_sizes);
+            let is_variable = !sizes.is_empty() || ty.vla_size().is_some();
+            inits.extend(sizes);
+            if attr.is_typedef {
+                // an alignment is of an object, not of a type.
+                if attr.align.is_some() {
+                    return Err(SError::new(name_start, SyntaxError::InvalidAlignment));
+                }
+                if !self.locals.declare_typedef(&name, ty) {
+                    return Err(SError::new(name_start, SyntaxError::Redeclared(name)));
+                }
+                continue;
+            }
+            if ty.func_type().is_some() {
+                if is_first
+                    && self.locals.is_file_scope()
+                    && matches!(self.cur_token().get_type(), TokenType::BraceL)
+                {
+                    self.parse_function(name, ty, &attr, name_start)?;
+                    return Ok(DeclStmt {
+                        loc: self.finish_loc(start),
+                        inits,
+                    });
+                }
+                // a function declared in a block still refers to one with linkage.
+                if attr.is_static && !self.locals.is_file_scope() {
+                    return Err(SError::new(name_start, SyntaxError::InvalidType));
+                }
+                self.parse_global(name, ty, &attr, name_start)?;
+                continue;
+            }
+            if self.locals.is_file_scope() || attr.is_static || attr.is_extern {
+                if is_variable {
+                    return Err(SError::new(name_start, SyntaxError::InvalidType));
+                }
+                self.parse_global(name, ty, &attr, name_start)?;
+                continue;
+            }
+            // the frame is only as aligned as the stack.
+            if attr.align_of(&ty) > 16 {
+                return Err(SError::new(name_start, SyntaxError::InvalidAlignment));
+            }
+            if ty.vla_size().is_some() {
+                inits.extend(self.declare_vla(name, ty, name_start)?);
+                continue;
+            }
+            // an array declared with `[]` is only declared once its initializer gives its length.
+            let mut init = None;
+            if ty.is_array() && !ty.is_complete() && self.eat(&TokenType::Assign)? {
+                init = Some(self.parse_initializer(&ty)?);
+            }
+            let ty = init.as_ref().map_or(ty, |init| init.ty.clone());
+            if !ty.is_complete() {
+                return Err(SError::new(name_start, SyntaxError::IncompleteType));
+            }
+            let var = match self.locals.declare(&name, ty.clone(), attr.align_of(&ty)) {
+                Some(var) => var.clone(),
+                None => return Err(SError::new(name_start, SyntaxError::Redeclared(name))),
+            };
+            if init.is_none() && self.eat(&TokenType::Assign)? {
+                init = Some(self.parse_initializer(&var.ty)?);
+            }
+            if let Some(init) = init {
+                let var = IdentExpr {
+                    loc: self.finish_loc(name_start.clone()),
+                    storage: Storage::Local(var.offset),
+                    ty: var.ty,
+                };
+                inits.extend(self.local_initializer(var, init)?);
+            }
+        }
+        Ok(DeclStmt {
+            loc: self.finish_loc(start),
+            inits,
+        })
+    }
+
+    /// static-assert = "_Static_assert" "(" const-expr ("," string)? ")" ";"
+    fn parse_static_assert(&mut self) -> SResult<()> {
+        let start = self.cur_token_start();
+        self.next()?;
+        self.expect(&TokenType::ParenL)?;
+        let expr = self.parse_maybe_assign()?;
+        let value = self.eval_const(&expr)?;
+        let message = if self.eat(&TokenType::Comma)? {
+            match self.cur_token().get_type() {
+                TokenType::Str(bytes) => {
+                    let message = String::from_utf8_lossy(bytes).into_owned();
+                    self.next()?;
+                    message
+                }
+                _ => return self.unexpected(self.cur_token()),
+            }
+        } else {
+            String::new()
+        };
+        self.expect(&TokenType::ParenR)?;
+        self.expect(&TokenType::Semi)?;
+        if value == 0 {
+            return Err(SError::new(start, SyntaxError::StaticAssert(message)));
+        }
+        Ok(())
+    }
+
+    /// declare a variable-length array, returning the allocation of its elements, which are
+    /// freed when the innermost block is left. It can not be initialized.
+    ///
+    /// Each saves the bottom of the allocations first, which a jump out of its scope restores.
+    /// The first one of a block saves it for the block, rather than the block on entry, as a
+    /// `goto` may enter the block after its start.
+    fn declare_vla(&mut self, name: String, ty: Type, start: Pos) -> SResult<Vec<Expr>> {
+        let var = match self.locals.declare(&name, ty.clone(), ty.align()) {
+            Some(var) => var.clone(),
+            None => return Err(SError::new(start, SyntaxError::Redeclared(name))),
+        };
+        if matches!(self.cur_token().get_type(), TokenType::Assign) {
+            return Err(SError::new(start, SyntaxError::InvalidType));
+        }
+        let loc = self.finish_loc(start);
+        let bottom = self.alloca_bottom();
+        let saved = self.locals.declare_anonymous(Type::ulong()).offset;
+        self.vlas.push(saved);
+        self.saved_bottom.get_or_insert(saved);
+        let mut inits = vec![Expr::Assign(AssignExpr {
+            loc: loc.clone(),
+            left: Box::new(LeftVal::Ident(hidden_local(
+                saved,
+                Type::ulong(),
+                loc.clone(),
+            ))),
+            right: Box::new(Expr::Ident(hidden_local(
+                bottom,
+                Type::ulong(),
+                loc.clone(),
+            ))),
+            ty: Type::ulong(),
+        })];
+        let size = hidden_local(ty.vla_size().unwrap(), Type::ulong(), loc.clone());
+        let alloca = Expr::Alloca(AllocaExpr {
+            loc: loc.clone(),
+            size: Box::new(Expr::Ident(size)),
+            bottom,
+            ty: Type::pointer_to(Type::void()),
+        });
+        // the variable itself holds the address of the elements.
+        let ptr_ty = ty.decay();
+        inits.push(Expr::Assign(AssignExpr {
+            loc: loc.clone(),
+            left: Box::new(LeftVal::Ident(hidden_local(
+                var.offset,
+                ptr_ty.clone(),
+                loc,
+            ))),
+            right: Box::new(cast(alloca, &ptr_ty)),
+            ty: ptr_ty,
+        }));
+        Ok(inits)
+    }
+
+    /// declare a variable with static storage duration, with an optional constant initializer.
+    ///
+    /// At file scope, every declaration of a name refers to the same global: it may be
+    /// declared `extern` and tentatively defined any number of times, but initialized only once.
+    ///
+    /// A function is declared the same way, and only its body defines it.
+    fn parse_global(&mut self, name: String, ty: Type, attr: &VarAttr, start: Pos) -> SResult<()> {
+        let is_func = ty.func_type().is_some();
+        let (ty, init) = if !is_func && self.eat(&TokenType::Assign)? {
+            let init = self.parse_initializer(&ty)?;
+            (init.ty.clone(), Some(self.global_data(&init)?))
+        } else {
+            (ty, None)
+        };
+        let is_definition = !is_func && (!attr.is_extern || init.is_some());
+        let align = attr.align_of(&ty);
+        if is_definition && !ty.is_complete() {
+            return Err(SError::new(start, SyntaxError::IncompleteType));
+        }
+
+        if !self.locals.is_file_scope() && attr.is_static {
+            // a block scope `static` is only visible in its block, so give it a unique label.
+            let label = format!(".L.{}.{}", name, self.locals.globals().len());
+            let index = self.locals.add_global(Global {
+                name: label,
+                ty,
+                is_static: true,
+                is_definition: true,
+                init,
+                align,
+            });
+            if !self.locals.declare_global(&name, index) {
+                return Err(SError::new(start, SyntaxError::Redeclared(name)));
+            }
+            return Ok(());
+        }
+        if init.is_some() && !self.locals.is_file_scope() {
+            return self.unexpected(self.last_token());
+        }
+
+        let index = match self.locals.find_global(&name) {
+            Some(index) => {
+                let global = self.locals.global_mut(index);
+                // `extern` keeps the linkage of a previous declaration, as do functions.
+                let is_static = attr.is_static || ((attr.is_extern || is_func) && global.is_static);
+                if !is_compatible(&global.ty, &ty) || global.is_static != is_static {
+                    return Err(SError::new(start, SyntaxError::Redeclared(name)));
+                }
+                // a later declaration may give the length of an array declared with `[]`,
+                // or the prototype of a function declared without one.
+                let is_prototype = |ty: &Type| ty.func_type().map(|func| func.is_prototype);
+                if !global.ty.is_complete() || is_prototype(&global.ty) < is_prototype(&ty) {
+                    global.ty = ty;
+                }
+                if init.is_some() {
+                    if global.init.is_some() {
+                        return Err(SError::new(start, SyntaxError::Redefined(name)));
+                    }
+                    global.init = init;
+                }
+                global.is_definition |= is_definition;
+                global.align = global.align.max(align);
+                index
+            }
+            None => self.locals.add_global(Global {
+                name: name.clone(),
+                ty,
+                is_static: attr.is_static,
+                is_definition,
+                init,
+                align,
+            }),
+        };
+        match self.locals.find_innermost(&name) {
+            Some(Symbol::Global(global)) if global.name == name => Ok(()),
+            Some(_) => Err(SError::new(start, SyntaxError::Redeclared(name))),
+            None => {
+                self.locals.declare_global(&name, index);
+                Ok(())
+            }
+        }
+    }
+
+    /// function-definition = declspec declarator "{" stmt* "}"
+    ///
+    /// the parameters are locals of the function, in the same scope as its outermost block.
+    fn parse_function(
+        &mut self,
+        name: String,
+        ty: Type,
+        attr: &VarAttr,
+        start: Pos,
+    ) -> SResult<()> {
+        self.parse_global(name.clone(), ty.clone(), attr, start.clone())?;
+        let index = self.locals.find_global(&name).unwrap();
+        let global = self.locals.global_mut(index);
+        if global.is_definition {
+            return Err(SError::new(start, SyntaxError::Redefined(name)));
+        }
+        global.is_definition = true;
+        let is_static = global.is_static;
+        let func = ty.func_type().unwrap().clone();
+        if !func.ret.is_complete() && !func.ret.is_void() {
+            return Err(SError::new(start, SyntaxError::IncompleteType));
+        }
+
+        let size = self.locals.enter_function();
+        let labels = std::mem::take(&mut self.labels);
+        let gotos = std::mem::take(&mut self.gotos);
+        let vlas = std::mem::take(&mut self.vlas);
+        let ret_ty = std::mem::replace(&mut self.ret_ty, func.ret.clone());
+        let alloca_bottom = self.alloca_bottom.take();
+        let mut params = vec![];
+        for (ty, name) in func.params.iter().zip(&func.names) {
+            if !ty.is_complete() {
+                return Err(SError::new(start, SyntaxError::IncompleteType));
+            }
+            if ty.align() > 16 {
+                return Err(SError::new(start, SyntaxError::InvalidAlignment));
+            }
+            let var = match name {
+                Some(name) => match self.locals.declare(name, ty.clone(), ty.align()) {
+                    Some(var) => var.clone(),
+                    None => return Err(SError::new(start, SyntaxError::Redeclared(name.clone()))),
+                },
+                None => self.locals.declare_anonymous(ty.clone()).clone(),
+            };
+            params.push(var);
+        }
+        // the six argument registers, then the eight vector registers in 16 byte slots.
+        let va_area = func.is_variadic.then(|| {
+            self.locals
+                .declare_anonymous(Type::array_of(Type::char(), 176))
+                .offset
+        });
+        self.va_area = va_area;
+        // where the caller wants a record returned in memory.
+        let ret_buffer = func.ret.record().map(|_| {
+            self.locals
+                .declare_anonymous(Type::pointer_to(func.ret.clone()))
+                .offset
+        });
+        let body = self.parse_block_body()?;
+        let function_labels = self.check_gotos()?;
+        self.labels = labels;
+        self.gotos = gotos;
+        self.vlas = vlas;
+        self.ret_ty = ret_ty;
+        self.va_area = None;
+        let alloca_bottom = std::mem::replace(&mut self.alloca_bottom, alloca_bottom);
+        let stack_size = align(self.locals.leave_function(size), 16);
+
+        self.functions.push(Function {
+            loc: self.finish_loc(start),
+            name,
+            is_static,
+            params,
+            ret: func.ret.clone(),
+            va_area,
+            ret_buffer,
+            alloca_bottom,
+            labels: function_labels,
+            body: vec![Stmt::Block(body)],
+            stack_size,
+        });
+        Ok(())
+    }
+
+    fn parse_tag(&mut self) -> SResult<Option<String>> {
+        match self.cur_token().get_type() {
+            TokenType::Name(name) => {
+                let name = name.to_string();
+                self.next()?;
+                Ok(Some(name))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// enum-decl = "enum" ident? ("{" ident ("=" const-expr)? ("," ident ("=" const-expr)?)* ","? "}")?
+    fn parse_enum_decl(&mut self) -> SResult<Type> {
+        let start = self.cur_token_start();
+        self.next()?;
+        let tag = self.parse_tag()?;
+        let ty = Type::enum_type();
+
+        if !matches!(self.cur_token().get_type(), TokenType::BraceL) {
+            let tag = match tag {
+                Some(tag) => tag,
+                None => return self.unexpected(self.cur_token()),
+            };
+            if let Some((ty, _)) = self.locals.find_tag(&tag) {
+                return check_tag_kind(ty.clone(), &TokenType::Enum, &tag, start);
+            }
+            self.locals.declare_tag(&tag, ty.clone());
+            return Ok(ty);
+        }
+
+        if let Some(tag) = &tag {
+            if let Some((_, true)) = self.locals.find_tag(tag) {
+                return Err(SError::new(start, SyntaxError::Redefined(tag.clone())));
+            }
+            self.locals.declare_tag(tag, ty.clone());
+        }
+
+        self.expect(&TokenType::BraceL)?;
+        let mut value: i64 = 0;
+        while !self.eat(&TokenType::BraceR)? {
+            let name_start = self.cur_token_start();
+            let name = self.parse_label()?;
+            if self.eat(&TokenType::Assign)? {
+                let expr = self.parse_maybe_assign()?;
+                value = self.eval_const(&expr)?;
+            }
+            if !self.locals.declare_enum(&name, value) {
+                return Err(SError::new(name_start, SyntaxError::Redeclared(name)));
+            }
+            value = value.wrapping_add(1);
+            if !self.eat(&TokenType::Comma)? {
+                self.expect(&TokenType::BraceR)?;
+                break;
+            }
+        }
+        Ok(ty)
+    }
+
+    /// record-decl = ("struct" | "union") attributes ident? (members attributes)?
+    ///
+    /// the `packed` and `aligned` attributes apply to the layout of the members.
+    fn parse_record_decl(&mut self) -> SResult<Type> {
+        let start = self.cur_token_start();
+        let keyword = self.cur_token().get_type().clone();
+        let is_union = keyword == TokenType::Union;
+        self.next()?;
+        let mut attr = VarAttr::default();
+        self.parse_attributes(&mut attr)?;
+        let tag = self.parse_tag()?;
+
+        if !matches!(self.cur_token().get_type(), TokenType::BraceL) {
+            // a reference to a tag, declaring it incomplete when it is not known yet.
+            let tag = match tag {
+                Some(tag) => tag,
+                None => return self.unexpected(self.cur_token()),
+            };
+            if let Some((ty, _)) = self.locals.find_tag(&tag) {
+                return check_tag_kind(ty.clone(), &keyword, &tag, start);
+            }
+            let ty = Type::record_of(RecordRef::new(Some(tag.clone()), is_union));
+            self.locals.declare_tag(&tag, ty.clone());
+            return Ok(ty);
+        }
+
+        let ty = match &tag {
+            Some(tag) => match self.locals.find_tag(tag) {
+                Some((ty, true)) if ty.is_complete() => {
+                    return Err(SError::new(start, SyntaxError::Redefined(tag.clone())));
+                }
+                Some((ty, true)) => check_tag_kind(ty.clone(), &keyword, tag, start)?,
+                _ => {
+                    let ty = Type::record_of(RecordRef::new(Some(tag.clone()), is_union));
+                    self.locals.declare_tag(tag, ty.clone());
+                    ty
+                }
+            },
+            None => Type::record_of(RecordRef::new(None, is_union)),
+        };
+        let members = self.parse_struct_members(is_union)?;
+        self.parse_attributes(&mut attr)?;
+        ty.record()
+            .unwrap()
+            .complete(members, attr.is_packed, attr.align);
+        Ok(ty)
+    }
+
+    /// members = "{" (declspec member ("," member)* ";" | static-assert)* "}"
+    /// member = (declarator (":" const-expr)? | ":" const-expr) attributes
+    ///
+    /// the last member of a struct with other named members may be a flexible array
+    /// member, an array declared with `[]` that takes no room in the struct.
+    fn parse_struct_members(&mut self, is_union: bool) -> SResult<Vec<MemberDecl>> {
+        self.expect(&TokenType::BraceL)?;
+        let mut members: Vec<MemberDecl> = vec![];
+        let mut flexible = None;
+        while !self.eat(&TokenType::BraceR)? {
+            if matches!(self.cur_token().get_type(), TokenType::StaticAssert) {
+                self.parse_static_assert()?;
+                continue;
+            }
+            let spec_start = self.cur_token_start();
+            let mut spec_attr = VarAttr::default();
+            let base = self.parse_declspec(Some(&mut spec_attr))?;
+            if spec_attr.is_typedef || spec_attr.is_static || spec_attr.is_extern {
+                return Err(SError::new(spec_start, SyntaxError::InvalidType));
+            }
+            let mut first = true;
+            while !self.eat(&TokenType::Semi)? {
+                if !first {
+                    self.expect(&TokenType::Comma)?;
+                }
+                first = false;
+
+                let start = self.cur_token_start();
+                if let Some(pos) = flexible {
+                    return Err(SError::new(pos, SyntaxError::IncompleteType));
+                }
+                let queued = self.vla_sizes.len();
+                // a bit-field may be unnamed.
+                let (name, ty) = if matches!(self.cur_token().get_type(), TokenType::Colon) {
+                    (None, base.clone())
+                } else {
+                    let (name, ty) = self.parse_declarator(base.clone())?;
+                    (Some(name), ty)
+                };
+                let has_named = members.iter().any(|member| member.name.is_some());
+                if matches!(ty.kind, TypeKind::Array(_, None)) && !is_union && has_named {
+                    flexible = Some(start.clone());
+                } else if !ty.is_complete() {
+                    return Err(SError::new(start, SyntaxError::IncompleteType));
+                }
+                // a member has a fixed offset, so its type has a fixed size.
+                if self.vla_sizes.len() > queued || ty.vla_size().is_some() {
+                    return Err(SError::new(start, SyntaxError::InvalidType));
+                }
+                let width = if self.eat(&TokenType::Colon)? {
+                    Some(self.parse_bit_width(&ty, name.is_some())?)
+                } else {
+                    None
+                };
+                let mut attr = spec_attr.clone();
+                self.parse_attributes(&mut attr)?;
+                if width.is_some() && attr.align.is_some() {
+                    return Err(SError::new(start, SyntaxError::InvalidBitField));
+                }
+                if let Some(name) = &name {
+                    if members
+                        .iter()
+                        .any(|member| member.name.as_ref() == Some(name))
+                    {
+                        return Err(SError::new(start, SyntaxError::Redeclared(name.clone())));
+                    }
+                }
+                members.push(MemberDecl {
+                    name,
+                    ty,
+                    width,
+                    align: attr.align,
+                    is_packed: attr.is_packed,
+                });
+            }
+        }
+        Ok(members)
+    }
+
+    /// the width of a bit-field of type `ty`, which is an integer type at least as wide.
+    /// Only an unnamed bit-field may have no bits.
+    fn parse_bit_width(&mut self, ty: &Type, is_named: bool) -> SResult<usize> {
+        let start = self.cur_token_start();
+        let expr = self.parse_expression()?;
+        let width = self.eval_const(&expr)?;
+        let max = if ty.kind == TypeKind::Bool {
+            1
+        } else {
+            ty.size() * 8
+        };
+        match usize::try_from(width) {
+            Ok(width) if ty.is_integer() && width <= max && (width > 0 || !is_named) => Ok(width),
+            _ => Err(SError::new(start, SyntaxError::InvalidBitField)),
+        }
+    }
+
+    /// declarator = pointers ("(" declarator ")" | ident) type-suffix
+    fn parse_declarator(&mut self, ty: Type) -> SResult<(String, Type)> {
+        match self.parse_param_declarator(ty)? {
+            (Some(name), ty) => Ok((name, ty)),
+            (None, _) => self.unexpected(self.cur_token()),
+        }
+    }
+
+    /// param-declarator = pointers ("(" param-declarator ")" | ident?) type-suffix
+    ///
+    /// the declarator of a parameter, which may leave out the name.
+    fn parse_param_declarator(&mut self, ty: Type) -> SResult<(Option<String>, Type)> {
+        let ty = self.parse_pointers(ty)?;
+        if self.is_nested_declarator()? {
+            return self.parse_nested_declarator(ty, Self::parse_param_declarator);
+        }
+        let name = match self.cur_token().get_type() {
+            TokenType::Name(name) => Some(name.to_string()),
+            _ => None,
+        };
+        if name.is_some() {
+            self.next()?;
+        }
+        let ty = self.parse_type_suffix(ty)?;
+        Ok((name, ty))
+    }
+
+    /// whether the current `(` encloses a declarator, as in `int (*fp)(int)`,
+    /// rather than the parameters of a function.
+    fn is_nested_declarator(&mut self) -> SResult<bool> {
+        if !matches!(self.cur_token().get_type(), TokenType::ParenL) {
+            return Ok(false);
+        }
+        Ok(match self.peek_token()?.get_type() {
+            TokenType::Star | TokenType::ParenL => true,
+            TokenType::Name(name) => self.locals.find_typedef(name).is_none(),
+            _ => false,
+        })
+    }
+
+    /// the declarator in parentheses applies to the type completed by the suffix after
+    /// them, so skip over it, parse the suffix, and come back to parse it with that type.
+    fn parse_nested_declarator<T>(
+        &mut self,
+        ty: Type,
+        parse: fn(&mut Self, Type) -> SResult<T>,
+    ) -> SResult<T> {
+        self.expect(&TokenType::ParenL)?;
+        let inner = self.checkpoint();
+        self.skip_parens()?;
+        let ty = self.parse_type_suffix(ty)?;
+        let after = self.checkpoint();
+        self.restore(inner);
+        let declarator = parse(self, ty)?;
+        self.expect(&TokenType::ParenR)?;
+        self.restore(after);
+        Ok(declarator)
+    }
+
+    /// type-suffix = "(" params ")" | "[" expr? "]" type-suffix | ε
+    ///
+    /// the length of an array is a constant, except in a block where it makes a
+    /// variable-length array.
+    fn parse_type_suffix(&mut self, ty: Type) -> SResult<Type> {
+        if matches!(self.cur_token().get_type(), TokenType::ParenL) {
+            return self.parse_params(ty);
+        }
+        if self.eat(&TokenType::BracketL)? {
+            let start = self.cur_token_start();
+            let mut vla_len = None;
+            // the length of an array declared with `[]` is left to its initializer.
+            let len = if self.eat(&TokenType::BracketR)? {
+                None
+            } else {
+                let expr = self.parse_expression()?;
+                let len = match self.eval_const(&expr) {
+                    Ok(len) => Some(
+                        usize::try_from(len)
+                            .map_err(|_| SError::new(start, SyntaxError::InvalidArraySize))?,
+                    ),
+                    Err(_) if !self.locals.is_file_scope() && expr.ty().is_integer() => {
+                        vla_len = Some(expr);
+                        None
+                    }
+                    Err(err) => return Err(err),
+                };
+                self.expect(&TokenType::BracketR)?;
+                len
+            };
+            let elem_start = self.cur_token_start();
+            let queued = self.vla_sizes.len();
+            let ty = self.parse_type_suffix(ty)?;
+            if !ty.is_complete() {
+                return Err(SError::new(elem_start, SyntaxError::IncompleteType));
+            }
+            if ty.func_type().is_some() {
+                return Err(SError::new(elem_start, SyntaxError::InvalidType));
+            }
+            if let Some(len) = vla_len {
+                let is_queued = self.vla_sizes.len() > queued;
+                return Ok(self.vla_of(ty, len, is_queued));
+            }
+            Ok(match len {
+                Some(len) => Type::array_of(ty, len),
+                None => Type::incomplete_array_of(ty),
+            })
+        } else {
+            Ok(ty)
+        }
+    }
+
+    /// a variable-length array of `len` elements of `base`, with its size computed into a new
+    /// local by an expression queued in `vla_sizes`. When `base` is a variable-length array
+    /// whose size is queued last, that computation is nested into the new one, so that the
+    /// size of an array type is computed by a single expression.
+    fn vla_of(&mut self, base: Type, len: Expr, is_queued: bool) -> Type {
+        let loc = len.loc();
+        let base_size = match base.vla_size() {
+            Some(_) if is_queued => self.vla_sizes.pop().unwrap(),
+            Some(size) => Expr::Ident(hidden_local(size, Type::ulong(), loc.clone())),
+            None => Expr::Literal(Lit::Int(IntLit {
+                loc: loc.clone(),
+                num: base.size().to_string(),
+                ty: Type::ulong(),
+            })),
+        };
+        let size = self.locals.declare_anonymous(Type::ulong()).offset;
+        let product = Expr::Binary(BinaryExpr {
+            loc: loc.clone(),
+            left: Box::new(cast(len, &Type::ulong())),
+            op: BinaryOp::Mul,
+            right: Box::new(base_size),
+            ty: Type::ulong(),
+        });
+        self.vla_sizes.push(Expr::Assign(AssignExpr {
+            loc: loc.clone(),
+            left: Box::new(LeftVal::Ident(hidden_local(size, Type::ulong(), loc))),
+            right: Box::new(product),
+            ty: Type::ulong(),
+        }));
+        Type::vla_of(base, size)
+    }
+
+    /// type-name = declspec abstract-declarator
+    ///
+    /// only `sizeof` takes a variably modified type name, through `parse_sized_type_name`.
+    pub(crate) fn parse_type_name(&mut self) -> SResult<Type> {
+        let start = self.cur_token_start();
+        let (ty, sizes) = self.parse_sized_type_name()?;
+        if !sizes.is_empty() {
+            return Err(SError::new(start, SyntaxError::InvalidType));
+        }
+        Ok(ty)
+    }
+
+    /// a type name, with the computations of the sizes of its variable-length arrays.
+    pub(crate) fn parse_sized_type_name(&mut self) -> SResult<(Type, Vec<Expr>)> {
+        let outer = std::mem::take(&mut self.vla_sizes);
+        let ty = self.parse_declspec(None)?;
+        let ty = self.parse_abstract_declarator(ty)?;
+        Ok((ty, std::mem::replace(&mut self.vla_sizes, outer)))
+    }
+
+    /// declspec = ("typedef" | "static" | "extern" | "const" | "volatile" | "restrict"
+    ///            | "_Bool" | "char" | "short" | "int" | "long" | "signed" | "unsigned"
+    ///            | record-decl | enum-decl | typedef-name | alignas | attributes)+
+    ///
+    /// storage-class and alignment specifiers are only allowed where `attr` is given,
+    /// elsewhere attributes are ignored.
+    fn parse_declspec(&mut self, mut attr: Option<&mut VarAttr>) -> SResult<Type> {
+        let start = self.cur_token_start();
+        // `void`, or the type of a record, an enum or a typedef name, which can not be
+        // combined with others.
+        let mut named: Option<Type> = None;
+        let (mut bool, mut char, mut short, mut int, mut long) = (0, 0, 0, 0, 0);
+        let (mut signed, mut unsigned) = (0, 0);
+        let mut qual = Qualifiers::default();
+        loop {
+            let seen = named.is_some() || bool + char + short + int + long + signed + unsigned > 0;
+            match self.cur_token().get_type() {
+                TokenType::Const => qual.is_const = true,
+                TokenType::Volatile => qual.is_volatile = true,
+                TokenType::Restrict => qual.is_restrict = true,
+                TokenType::Typedef | TokenType::Static | TokenType::Extern => {
+                    let attr = match attr.as_deref_mut() {
+                        Some(attr) => attr,
+                        None => return self.unexpected(self.cur_token()),
+                    };
+                    if attr.is_typedef || attr.is_static || attr.is_extern {
+                        return Err(SError::new(start, SyntaxError::InvalidType));
+                    }
+                    match self.cur_token().get_type() {
+                        TokenType::Typedef => attr.is_typedef = true,
+                        TokenType::Static => attr.is_static = true,
+                        _ => attr.is_extern = true,
+                    }
+                }
+                TokenType::Alignas => {
+                    let attr = match attr.as_deref_mut() {
+                        Some(attr) => attr,
+                        None => return self.unexpected(self.cur_token()),
+                    };
+                    let align = self.parse_alignas()?;
+                    attr.align = attr.align.max(Some(align));
+                    continue;
+                }
+                TokenType::Attribute => {
+                    let mut ignored = VarAttr::default();
+                    self.parse_attributes(attr.as_deref_mut().unwrap_or(&mut ignored))?;
+                    continue;
+                }
+                TokenType::Void | TokenType::Struct | TokenType::Union | TokenType::Enum
+                    if seen =>
+                {
+                    return Err(SError::new(start, SyntaxError::InvalidType));
+                }
+                TokenType::Struct | TokenType::Union => {
+                    named = Some(self.parse_record_decl()?);
+                    continue;
+                }
+                TokenType::Enum => {
+                    named = Some(self.parse_enum_decl()?);
+                    continue;
+                }
+                // once the type is known, a typedef name is the declarator redeclaring it.
+                TokenType::Name(name) if !seen => match self.locals.find_typedef(name) {
+                    Some(ty) => named = Some(ty.clone()),
+                    None => break,
+                },
+                TokenType::Void => named = Some(Type::void()),
+                TokenType::Bool => bool += 1,
+                TokenType::Char => char += 1,
+                TokenType::Short => short += 1,
+                TokenType::Int => int += 1,
+                TokenType::Long => long += 1,
+                TokenType::Signed => signed += 1,
+                TokenType::Unsigned => unsigned += 1,
+                _ => break,
+            }
+            self.next()?;
+        }
+        let invalid = || Err(SError::new(start, SyntaxError::InvalidType));
+        // `signed` and `unsigned` alone mean `int`. `signed char` is not `char`.
+        let ty = match (named, bool, char, short, int, long) {
+            (Some(ty), 0, 0, 0, 0, 0) if signed + unsigned == 0 => ty,
+            (None, 1, 0, 0, 0, 0) if signed + unsigned == 0 => Type::bool(),
+            (None, 0, 1, 0, 0, 0) if signed == 1 => Type::schar(),
+            (None, 0, 1, 0, 0, 0) => Type::char(),
+            (None, 0, 0, 1, 0 | 1, 0) => Type::short(),
+            (None, 0, 0, 0, 0 | 1, 0) if int + signed + unsigned > 0 => Type::int(),
+            (None, 0, 0, 0, 0 | 1, 1) => Type::long(),
+            (None, 0, 0, 0, 0 | 1, 2) => Type::long_long(),
+            (None, 0, 0, 0, 0, 0) if signed + unsigned == 0 => {
+                return self.unexpected(self.cur_token())
+            }
+            _ => return invalid(),
+        };
+        let ty = match (signed, unsigned) {
+            (0, 0) | (1, 0) => ty,
+            (0, 1) => ty.to_unsigned(),
+            _ => return invalid(),
+        };
+        if qual.is_restrict && !matches!(ty.kind, TypeKind::Ptr(_)) {
+            return invalid();
+        }
+        Ok(ty.qualified(qual))
+    }
+
+    /// alignas = "_Alignas" "(" (type-name | const-expr) ")"
+    ///
+    /// an alignment of 0 has no effect.
+    fn parse_alignas(&mut self) -> SResult<usize> {
+        self.next()?;
+        if self.is_paren_type_name()? {
+            return Ok(self.parse_paren_type_name()?.align());
+        }
+        self.expect(&TokenType::ParenL)?;
+        let align = self.parse_alignment()?;
+        self.expect(&TokenType::ParenR)?;
+        Ok(align)
+    }
+
+    /// a constant alignment, which is a power of two.
+    fn parse_alignment(&mut self) -> SResult<usize> {
+        let start = self.cur_token_start();
+        let expr = self.parse_expression()?;
+        match usize::try_from(self.eval_const(&expr)?) {
+            Ok(0) => Ok(1),
+            Ok(align) if align.is_power_of_two() => Ok(align),
+            _ => Err(SError::new(start, SyntaxError::InvalidAlignment)),
+        }
+    }
+
+    /// attributes = ("__attribute__" "(" "(" (attribute ("," attribute)*)? ")" ")")*
+    /// attribute = ident ("(" expr ("," expr)* ")")?
+    ///
+    /// only `packed` and `aligned`, with an optional alignment that defaults to 16, are
+    /// understood. Others are ignored with a warning.
+    fn parse_attributes(&mut self, attr: &mut VarAttr) -> SResult<()> {
+        while self.eat(&TokenType::Attribute)? {
+            self.expect(&TokenType::ParenL)?;
+            self.expect(&TokenType::ParenL)?;
+            let mut first = true;
+            while !self.eat(&TokenType::ParenR)? {
+                if !first {
+                    self.expect(&TokenType::Comma)?;
+                }
+                first = false;
+                let start = self.cur_token_start();
+                // `__packed__` is the same as `packed`, and keywords such as `const` may
+                // name attributes as well.
+                let name = match self.cur_token().get_type() {
+                    TokenType::Name(name) => name.trim_matches('_').to_string(),
+                    TokenType::Comma | TokenType::ParenR => {
+                        return self.unexpected(self.cur_token())
+                    }
+                    _ => String::new(),
+                };
+                self.next()?;
+                match name.as_str() {
+                    "packed" => attr.is_packed = true,
+                    "aligned" => {
+                        let align = if self.eat(&TokenType::ParenL)? {
+                            let align = self.parse_alignment()?;
+                            self.expect(&TokenType::ParenR)?;
+                            align
+                        } else {
+                            16
+                        };
+                        attr.align = attr.align.max(Some(align));
+                    }
+                    _ => {
+                        self.warnings
+                            .push(SWarning::new(start, Warning::IgnoredAttribute));
+                        if self.eat(&TokenType::ParenL)? {
+                            self.skip_parens()?;
+                        }
+                    }
+                }
+            }
+            self.expect(&TokenType::ParenR)?;
+        }
+        Ok(())
+    }
+
+    /// skip to the `)` closing an already opened `(`.
+    fn skip_parens(&mut self) -> SResult<()> {
+        let mut depth = 1;
+        while depth > 0 {
+            match self.cur_token().get_type() {
+                TokenType::ParenL => depth += 1,
+                TokenType::ParenR => depth -= 1,
+                TokenType::Eof => return self.unexpected(self.cur_token()),
+                _ => {}
+            }
+            self.next()?;
+        }
+        Ok(())
+    }
+
+    /// pointers = ("*" ("const" | "volatile" | "restrict")*)*
+    fn parse_pointers(&mut self, ty: Type) -> SResult<Type> {
+        let mut ty = ty;
+        while self.eat(&TokenType::Star)? {
+            let mut qual = Qualifiers::default();
+            loop {
+                match self.cur_token().get_type() {
+                    TokenType::Const => qual.is_const = true,
+                    TokenType::Volatile => qual.is_volatile = true,
+                    TokenType::Restrict => qual.is_restrict = true,
+                    _ => break,
+                }
+                self.next()?;
+            }
+            ty = Type::pointer_to(ty).qualified(qual);
+        }
+        Ok(ty)
+    }
+
+    /// params = "void" | (param ("," param)* ("," "...")?)?
+    /// param = declspec param-declarator
+    ///
+    /// empty parentheses declare a function without a prototype, unlike `(void)`.
+    fn parse_params(&mut self, ret: Type) -> SResult<Type> {
+        let start = self.cur_token_start();
+        self.expect(&TokenType::ParenL)?;
+        let (mut params, mut names) = (vec![], vec![]);
+        let mut is_variadic = false;
+        let is_prototype = !matches!(self.cur_token().get_type(), TokenType::ParenR);
+        if matches!(self.cur_token().get_type(), TokenType::Void)
+            && matches!(self.peek_token()?.get_type(), TokenType::ParenR)
+        {
+            self.next()?;
+        }
+        while !self.eat(&TokenType::ParenR)? {
+            if !params.is_empty() {
+                self.expect(&TokenType::Comma)?;
+            }
+            if self.eat(&TokenType::Ellipsis)? {
+                is_variadic = true;
+                self.expect(&TokenType::ParenR)?;
+                break;
+            }
+            let param_start = self.cur_token_start();
+            let queued = self.vla_sizes.len();
+            let base = self.parse_declspec(None)?;
+            let (name, ty) = self.parse_param_declarator(base)?;
+            if ty.is_void() {
+                return Err(SError::new(param_start, SyntaxError::InvalidType));
+            }
+            // the parameters are not in scope yet to give the length of an array.
+            if self.vla_sizes.len() > queued {
+                return Err(SError::new(param_start, SyntaxError::NotConstant));
+            }
+            // a parameter declared as an array or a function is a pointer.
+            let ty = if ty.is_array() || ty.func_type().is_some() {
+                ty.decay()
+            } else {
+                ty
+            };
+            params.push(ty);
+            names.push(name);
+        }
+        // a function can return neither an array nor a function.
+        if ret.is_array() || ret.func_type().is_some() {
+            return Err(SError::new(start, SyntaxError::InvalidType));
+        }
+        Ok(Type::func(
+            ret.unqualified(),
+            params,
+            names,
+            is_variadic,
+            is_prototype,
+        ))
+    }
+
+    /// abstract-declarator = pointers ("(" abstract-declarator ")")? type-suffix
+    fn parse_abstract_declarator(&mut self, ty: Type) -> SResult<Type> {
+        let ty = self.parse_pointers(ty)?;
+        if self.is_nested_declarator()? {
+            return self.parse_nested_declarator(ty, Self::parse_abstract_declarator);
+        }
+        self.parse_type_suffix(ty)
+    }
+}
+
+/// `struct`, `union` and `enum` share one tag namespace.
+fn check_tag_kind(ty: Type, keyword: &TokenType, tag: &str, start: Pos) -> SResult<Type> {
+    let tag_keyword = match ty.record() {
+        Some(record) if record.borrow().is_union => TokenType::Union,
+        Some(_) => TokenType::Struct,
+        None => TokenType::Enum,
+    };
+    if tag_keyword == *keyword {
+        Ok(ty)
+    } else {
+        Err(SError::new(
+            start,
+            SyntaxError::WrongTagKind(tag.to_string()),
+        ))
+    }
+}
+
+/// two declarations of a global agree on its type, up to the length of an array.
+fn is_compatible(left: &Type, right: &Type) -> bool {
+    match (&left.kind, &right.kind) {
+        (TypeKind::Array(left, None), TypeKind::Array(right, _))
+        | (TypeKind::Array(left, _), TypeKind::Array(right, None)) => left.is_compatible(right),
+        _ => left.is_compatible(right),
+    }
+}
+
+/// a local that no name refers to, such as the size of a variable-length array.
+pub(crate) fn hidden_local(offset: Offset, ty: Type, loc: Loc) -> IdentExpr {
+    IdentExpr {
+        loc,
+        storage: Storage::Local(offset),
+        ty,
+    }
+}
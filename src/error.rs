@@ -1,6 +1,7 @@
-use crate::token::Token;
+use std::fmt;
+
+use crate::{token::Token, utils::Pos};
 
-type Pos = usize;
 #[derive(Debug)]
 pub struct SError {
     inner: (Pos, SyntaxError),
@@ -19,4 +20,152 @@ pub enum SyntaxError {
     UnexpectedChar,
     UnexpectedToken(Token),
     CastWrong,
+    NotConstant,
+    DuplicateCase(i64),
+    DuplicateDefault,
+    CaseOutsideSwitch,
+    BreakOutsideLoop,
+    UndefinedLabel(String),
+    DuplicateLabel(String),
+    InvalidType,
+    InvalidArraySize,
+    Redeclared(String),
+    Redefined(String),
+    WrongTagKind(String),
+    IncompleteType,
+    UnknownMember(String),
+    IncompatibleTypes,
+    InvalidDesignator,
+    /// an assignment to a `const` object.
+    AssignToConst,
+    /// a pointer conversion that drops `const`, `volatile` or `restrict` from what it points to.
+    DiscardsQualifiers,
+    /// a call of something that is neither a function nor a pointer to one.
+    NotFunction,
+    /// `__builtin_va_start` outside of a variadic function.
+    NotVariadic,
+    /// a call of a name that is not declared.
+    Undeclared(String),
+    /// a call with fewer arguments than the prototype has parameters.
+    TooFewArguments,
+    /// a call with more arguments than the prototype has parameters, which is not variadic.
+    TooManyArguments,
+    /// a bit-field that is not of an integer type, is wider than its type, is named
+    /// but has no bits, or is given an alignment. Nor can its address or size be taken.
+    InvalidBitField,
+    /// a `_Generic` selection without an association for the type of its controlling
+    /// expression, nor a `default` one.
+    NoGenericAssociation,
+    /// two associations of a `_Generic` selection for the same type.
+    DuplicateAssociation,
+    /// a `_Static_assert` whose condition is 0, with its message.
+    StaticAssert(String),
+    /// an alignment that is not a power of two, or a local aligned to more than the
+    /// 16 bytes the stack frame is aligned to.
+    InvalidAlignment,
+    /// an `asm` statement with an unknown constraint, clobber or `%` substitution, an
+    /// operand that has no register left, or a register both clobbered and asked for.
+    InvalidAsm,
+    /// a `goto`, `case` or `default` jumping into the scope of a variable-length array,
+    /// past its allocation.
+    JumpIntoVla,
+    /// a binary operator applied to operands it does not take, such as two pointers
+    /// multiplied.
+    InvalidOperands,
+}
+
+impl fmt::Display for SError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (pos, error) = &self.inner;
+        write!(f, "{}: error: {}", pos, error)
+    }
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use SyntaxError::*;
+        match self {
+            UnexpectedChar => write!(f, "unexpected character"),
+            UnexpectedToken(token) => write!(f, "unexpected token {:?}", token.get_type()),
+            CastWrong => write!(f, "invalid operand"),
+            NotConstant => write!(f, "not a constant expression"),
+            DuplicateCase(value) => write!(f, "duplicate case value {}", value),
+            DuplicateDefault => write!(f, "duplicate default label"),
+            CaseOutsideSwitch => write!(f, "case label not within a switch statement"),
+            BreakOutsideLoop => write!(f, "break statement not within a loop or switch"),
+            UndefinedLabel(name) => write!(f, "label `{}` used but not defined", name),
+            DuplicateLabel(name) => write!(f, "duplicate label `{}`", name),
+            InvalidType => write!(f, "invalid type"),
+            InvalidArraySize => write!(f, "invalid array size"),
+            Redeclared(name) => write!(f, "`{}` redeclared", name),
+            Redefined(name) => write!(f, "`{}` redefined", name),
+            WrongTagKind(name) => write!(f, "`{}` defined as the wrong kind of tag", name),
+            IncompleteType => write!(f, "incomplete type"),
+            UnknownMember(name) => write!(f, "no member named `{}`", name),
+            IncompatibleTypes => write!(f, "incompatible types"),
+            InvalidDesignator => write!(f, "invalid designator"),
+            AssignToConst => write!(f, "assignment of a read-only object"),
+            DiscardsQualifiers => write!(f, "conversion discards qualifiers"),
+            NotFunction => write!(f, "called object is not a function"),
+            NotVariadic => write!(f, "va_start used in a function with fixed arguments"),
+            Undeclared(name) => write!(f, "`{}` undeclared", name),
+            TooFewArguments => write!(f, "too few arguments to function"),
+            TooManyArguments => write!(f, "too many arguments to function"),
+            InvalidBitField => write!(f, "invalid bit-field"),
+            NoGenericAssociation => write!(f, "no association matches the _Generic selection"),
+            DuplicateAssociation => write!(f, "duplicate association in _Generic selection"),
+            StaticAssert(message) => write!(f, "static assertion failed: \"{}\"", message),
+            InvalidAlignment => write!(f, "invalid alignment"),
+            InvalidAsm => write!(f, "invalid asm statement"),
+            JumpIntoVla => write!(f, "jump into the scope of a variable-length array"),
+            InvalidOperands => write!(f, "invalid operands to binary operator"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SWarning {
+    inner: (Pos, Warning),
+}
+
+impl SWarning {
+    pub fn new(pos: Pos, warning: Warning) -> Self {
+        Self {
+            inner: (pos, warning),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Warning {
+    /// an integer is implicitly converted to a pointer.
+    PointerFromInt,
+    /// a pointer is implicitly converted to an integer.
+    IntFromPointer,
+    /// a pointer is implicitly converted to an incompatible pointer.
+    IncompatiblePointer,
+    /// an initializer list has more elements than the object it initializes.
+    ExcessInitializer,
+    /// an `__attribute__` that has no effect here, which is ignored.
+    IgnoredAttribute,
+}
+
+impl fmt::Display for SWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (pos, warning) = &self.inner;
+        write!(f, "{}: warning: {}", pos, warning)
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self {
+            Warning::PointerFromInt => "pointer made from an integer without a cast",
+            Warning::IntFromPointer => "integer made from a pointer without a cast",
+            Warning::IncompatiblePointer => "conversion between incompatible pointer types",
+            Warning::ExcessInitializer => "excess elements in initializer",
+            Warning::IgnoredAttribute => "attribute ignored",
+        };
+        f.write_str(message)
+    }
 }
@@ -1,8 +1,12 @@
 use crate::{
     ast::*,
+    conversion::{cast, decay, usual_arith},
+    declaration::hidden_local,
     error::{SError, SyntaxError},
+    object::{Data, Global, Offset, Storage, Symbol},
     state::{SResult, State},
     token::{Token, TokenType},
+    ty::{Type, TypeKind},
     utils::{Loc, Pos},
 };
 
@@ -29,7 +33,7 @@ impl State {
         self.parse_maybe_assign()
     }
 
-    fn parse_maybe_assign(&mut self) -> SResult<Expr> {
+    pub(crate) fn parse_maybe_assign(&mut self) -> SResult<Expr> {
         let start = self.cur_token_start();
         let left = self.parse_operations()?;
         let tt = self.cur_token().get_type();
@@ -37,27 +41,61 @@ impl State {
             self.next()?;
             let right = self.parse_maybe_assign()?;
             let loc = self.finish_loc(start);
-            // expr to left
-            let left = match left {
-                Expr::Ident(expr) => Box::new(LeftVal::Ident(expr)),
-                Expr::Deref(expr) => Box::new(LeftVal::Deref(expr)),
-                _ => {
-                    return Err(SError::new(
-                        left.loc().get_start().pos,
-                        SyntaxError::CastWrong,
-                    ))
-                }
-            };
-            Ok(Expr::Assign(AssignExpr {
-                loc,
-                left,
-                right: Box::new(right),
-            }))
+            self.assign(left, right, loc)
         } else {
             Ok(left)
         }
     }
 
+    /// `left = right`, with `right` converted to the type of `left`.
+    pub(crate) fn assign(&mut self, left: Expr, right: Expr, loc: Loc) -> SResult<Expr> {
+        if left.ty().has_const() {
+            return Err(SError::new(
+                left.loc().get_start().clone(),
+                SyntaxError::AssignToConst,
+            ));
+        }
+        self.initialize(left, right, loc)
+    }
+
+    /// store the initial value `right` to `left`, which unlike an assignment may be `const`.
+    pub(crate) fn initialize(&mut self, left: Expr, right: Expr, loc: Loc) -> SResult<Expr> {
+        let ty = left.ty().unqualified();
+        if (ty.record().is_some() || right.ty().record().is_some())
+            && ty != right.ty().unqualified()
+        {
+            return Err(SError::new(
+                right.loc().get_start().clone(),
+                SyntaxError::IncompatibleTypes,
+            ));
+        }
+        let right = self.convert(right, &ty)?;
+        // expr to left
+        let left = match left {
+            _ if ty.is_array() => {
+                return Err(SError::new(
+                    left.loc().get_start().clone(),
+                    SyntaxError::CastWrong,
+                ))
+            }
+            Expr::Ident(expr) => Box::new(LeftVal::Ident(expr)),
+            Expr::Deref(expr) => Box::new(LeftVal::Deref(expr)),
+            Expr::Member(expr) => Box::new(LeftVal::Member(expr)),
+            _ => {
+                return Err(SError::new(
+                    left.loc().get_start().clone(),
+                    SyntaxError::CastWrong,
+                ))
+            }
+        };
+        Ok(Expr::Assign(AssignExpr {
+            loc,
+            left,
+            right: Box::new(right),
+            ty,
+        }))
+    }
+
     fn parse_operations(&mut self) -> SResult<Expr> {
         let start = self.cur_token_start();
         let expr = self.parse_maybe_unary()?;
@@ -77,18 +115,12 @@ impl State {
             self.next()?;
             let right_start = self.cur_token_start();
             let right_expr = self.parse_maybe_unary()?;
-            let right = Box::new(self.parse_operation(right_expr, right_start, prec)?);
+            let right = self.parse_operation(right_expr, right_start, prec)?;
             let loc = self.finish_loc(left_start.clone());
-            let left = Box::new(left);
             let expr = match tt {
                 TokenType::Plus => self.add_binary(left, token, right, loc)?,
                 TokenType::Minus => self.sub_binary(left, token, right, loc)?,
-                _ => BinaryExpr {
-                    left,
-                    right,
-                    op: tt.binary_op(),
-                    loc,
-                },
+                _ => self.arith_binary(left, tt.binary_op(), right, loc)?,
             };
             self.parse_operation(Expr::Binary(expr), left_start, min_prec)
         } else {
@@ -96,75 +128,119 @@ impl State {
         }
     }
 
+    /// operands of `*`, `/` and comparisons go through the usual arithmetic conversions.
+    fn arith_binary(
+        &mut self,
+        left: Expr,
+        op: BinaryOp,
+        right: Expr,
+        loc: Loc,
+    ) -> SResult<BinaryExpr> {
+        expect_scalar(&left)?;
+        expect_scalar(&right)?;
+        // pointers are only compared here, as `+` and `-` do their arithmetic.
+        if !op.is_comparison() && (left.ty().is_ptr() || right.ty().is_ptr()) {
+            return Err(SError::new(
+                loc.get_start().clone(),
+                SyntaxError::InvalidOperands,
+            ));
+        }
+        let (left, right, ty) = match (left.ty().is_ptr(), right.ty().is_ptr()) {
+            (false, false) => usual_arith(left, right),
+            // ptr1 == ptr2
+            (true, true) => {
+                let (left, right) = (decay(left), decay(right));
+                let ty = left.ty().clone();
+                (left, right, ty)
+            }
+            // ptr == num
+            (true, false) => {
+                let left = decay(left);
+                let ty = left.ty().clone();
+                let right = self.convert(right, &ty)?;
+                (left, right, ty)
+            }
+            // num == ptr
+            (false, true) => {
+                let right = decay(right);
+                let ty = right.ty().clone();
+                let left = self.convert(left, &ty)?;
+                (left, right, ty)
+            }
+        };
+        let ty = if op.is_comparison() { Type::int() } else { ty };
+        Ok(BinaryExpr {
+            left: Box::new(left),
+            right: Box::new(right),
+            op,
+            loc,
+            ty,
+        })
+    }
+
     fn add_binary(
-        &self,
-        left: Box<Expr>,
+        &mut self,
+        left: Expr,
         token: Token,
-        right: Box<Expr>,
+        right: Expr,
         loc: Loc,
     ) -> SResult<BinaryExpr> {
-        match (contain_addr(&left), contain_addr(&right)) {
+        match (left.ty().is_ptr(), right.ty().is_ptr()) {
             // ptr1 + ptr2
             (true, true) => Err(SError::new(
-                token.get_start().pos,
+                token.get_start().clone(),
                 SyntaxError::UnexpectedToken(token),
             )),
             // num1 + num2
-            (false, false) => Ok(BinaryExpr {
-                left,
-                right,
-                op: BinaryOp::Add,
-                loc,
-            }),
+            (false, false) => self.arith_binary(left, BinaryOp::Add, right, loc),
             // ptr + num
             (true, false) => Ok(BinaryExpr {
-                left,
+                ty: left.ty().decay(),
+                left: Box::new(decay(left)),
                 op: BinaryOp::AddrAdd(BinaryAddrPos::Left),
-                right,
+                right: Box::new(cast(right, &Type::long())),
                 loc,
             }),
             // num + ptr
             (false, true) => Ok(BinaryExpr {
-                left,
+                ty: right.ty().decay(),
+                left: Box::new(cast(left, &Type::long())),
                 op: BinaryOp::AddrAdd(BinaryAddrPos::Right),
-                right,
+                right: Box::new(decay(right)),
                 loc,
             }),
         }
     }
 
     fn sub_binary(
-        &self,
-        left: Box<Expr>,
+        &mut self,
+        left: Expr,
         token: Token,
-        right: Box<Expr>,
+        right: Expr,
         loc: Loc,
     ) -> SResult<BinaryExpr> {
-        match (contain_addr(&left), contain_addr(&right)) {
+        match (left.ty().is_ptr(), right.ty().is_ptr()) {
             // num1 - num2
-            (false, false) => Ok(BinaryExpr {
-                loc,
-                left,
-                op: BinaryOp::Sub,
-                right,
-            }),
+            (false, false) => self.arith_binary(left, BinaryOp::Sub, right, loc),
             // ptr - num
             (true, false) => Ok(BinaryExpr {
                 loc,
-                left,
+                ty: left.ty().decay(),
+                left: Box::new(decay(left)),
                 op: BinaryOp::AddrSub(BinaryAddrPos::Left),
-                right,
+                right: Box::new(cast(right, &Type::long())),
             }),
             // ptr - ptr
             (true, true) => Ok(BinaryExpr {
                 loc,
-                left,
+                ty: Type::long(),
+                left: Box::new(decay(left)),
                 op: BinaryOp::AddrSub(BinaryAddrPos::Both),
-                right,
+                right: Box::new(decay(right)),
             }),
             // num - ptr
             (false, true) => Err(SError::new(
-                token.get_start().pos,
+                token.get_start().clone(),
                 SyntaxError::UnexpectedToken(token),
             )),
         }
@@ -176,48 +252,382 @@ impl State {
         let expr = if tt.prefix() {
             self.next()?;
             let argument = self.parse_maybe_unary()?;
+            expect_scalar(&argument)?;
             let loc = self.finish_loc(start);
+            let ty = argument.ty().promote();
             Expr::Unary(UnaryExpr {
                 loc,
                 op: tt,
-                argument: Box::new(argument),
-                prefix: true,
+                argument: Box::new(cast(argument, &ty)),
+                ty,
             })
         } else if tt.eq(&TokenType::And) {
             self.next()?;
             let argument = self.parse_maybe_unary()?;
+            if !matches!(
+                argument,
+                Expr::Ident(_) | Expr::Deref(_) | Expr::Member(_) | Expr::CompoundLit(_)
+            ) {
+                return Err(SError::new(
+                    argument.loc().get_start().clone(),
+                    SyntaxError::CastWrong,
+                ));
+            }
+            expect_not_bit_field(&argument)?;
             let loc = self.finish_loc(start);
             Expr::Addr(AddrExpr {
                 loc,
+                ty: Type::pointer_to(argument.ty().clone()),
                 argument: Box::new(argument),
             })
         } else if tt.eq(&TokenType::Star) {
             self.next()?;
             let argument = self.parse_maybe_unary()?;
             let loc = self.finish_loc(start);
-            Expr::Deref(DerefExpr {
-                loc,
+            self.deref(argument, loc)?
+        } else if self.is_paren_type_name()? {
+            let ty = self.parse_paren_type_name()?;
+            if matches!(self.cur_token().get_type(), TokenType::BraceL) {
+                let literal = self.parse_compound_literal(ty, start.clone())?;
+                return self.parse_postfix(literal, start);
+            }
+            let argument = self.parse_maybe_unary()?;
+            // any value may be discarded by a cast to `void`, and otherwise only scalars
+            // are converted to scalars.
+            let is_scalar = ty.is_scalar() && argument.ty().decay().is_scalar();
+            if !ty.is_void() && !is_scalar {
+                return Err(SError::new(
+                    argument.loc().get_start().clone(),
+                    SyntaxError::CastWrong,
+                ));
+            }
+            Expr::Cast(CastExpr {
+                loc: self.finish_loc(start),
                 argument: Box::new(argument),
+                ty,
             })
+        } else if tt.eq(&TokenType::Sizeof) {
+            self.parse_sizeof()?
+        } else if tt.eq(&TokenType::Alignof) {
+            self.parse_alignof()?
         } else {
-            self.parse_atom()?
+            let atom = self.parse_atom()?;
+            self.parse_postfix(atom, start)?
         };
         Ok(expr)
     }
 
+    fn deref(&mut self, argument: Expr, loc: Loc) -> SResult<Expr> {
+        // dereferencing an integer reads a `long`, as the untyped locals hold addresses.
+        let argument = if argument.ty().is_ptr() {
+            decay(argument)
+        } else {
+            self.convert(argument, &Type::pointer_to(Type::long()))?
+        };
+        let ty = argument.ty().base().unwrap().clone();
+        Ok(Expr::Deref(DerefExpr {
+            loc,
+            argument: Box::new(argument),
+            ty,
+        }))
+    }
+
+    fn parse_member(&mut self, base: Expr, start: Pos) -> SResult<Expr> {
+        let name_start = self.cur_token_start();
+        let name = self.parse_label()?;
+        let member = base
+            .ty()
+            .record()
+            .filter(|record| record.borrow().complete)
+            .map(|record| record.borrow().find(&name).cloned());
+        let member = match member {
+            Some(Some(member)) => member,
+            Some(None) => return Err(SError::new(name_start, SyntaxError::UnknownMember(name))),
+            None => return Err(SError::new(name_start, SyntaxError::IncompleteType)),
+        };
+        // a member of a qualified record is qualified as well.
+        let ty = member.ty.qualified(base.ty().qual);
+        Ok(Expr::Member(MemberExpr {
+            loc: self.finish_loc(start),
+            base: Box::new(base),
+            offset: member.offset,
+            bit_field: member.bit_field,
+            ty,
+        }))
+    }
+
+    /// postfix = atom ("[" expr "]" | "." ident | "->" ident | "(" args ")")*
+    fn parse_postfix(&mut self, expr: Expr, start: Pos) -> SResult<Expr> {
+        let mut expr = expr;
+        loop {
+            let token = self.cur_token().clone();
+            match token.get_type() {
+                // `a[i]` is `*(a + i)`
+                TokenType::BracketL => {
+                    self.next()?;
+                    let index = self.parse_expression()?;
+                    self.expect(&TokenType::BracketR)?;
+                    let loc = self.finish_loc(start.clone());
+                    let sum = self.add_binary(expr, token, index, loc.clone())?;
+                    expr = self.deref(Expr::Binary(sum), loc)?;
+                }
+                TokenType::Dot => {
+                    self.next()?;
+                    expr = self.parse_member(expr, start.clone())?;
+                }
+                // `p->x` is `(*p).x`
+                TokenType::Arrow => {
+                    self.next()?;
+                    let loc = self.finish_loc(start.clone());
+                    let base = self.deref(expr, loc)?;
+                    expr = self.parse_member(base, start.clone())?;
+                }
+                TokenType::ParenL => {
+                    expr = self.parse_call(expr, start.clone())?;
+                }
+                _ => return Ok(expr),
+            }
+        }
+    }
+
+    /// args = (assign ("," assign)*)?
+    ///
+    /// the arguments are converted to the parameters of the prototype, as if by assignment.
+    /// The rest, and all those of a function without a prototype, are passed with integer
+    /// promotion.
+    fn parse_call(&mut self, callee: Expr, start: Pos) -> SResult<Expr> {
+        // a pointer to a function calls the function it points to.
+        let callee = match callee.ty().base() {
+            Some(base) if base.func_type().is_some() => {
+                let loc = callee.loc();
+                self.deref(callee, loc)?
+            }
+            _ => callee,
+        };
+        let func = match callee.ty().func_type() {
+            Some(func) => func.clone(),
+            None => {
+                return Err(SError::new(
+                    callee.loc().get_start().clone(),
+                    SyntaxError::NotFunction,
+                ))
+            }
+        };
+        self.expect(&TokenType::ParenL)?;
+        let mut args = vec![];
+        while !self.eat(&TokenType::ParenR)? {
+            if !args.is_empty() {
+                self.expect(&TokenType::Comma)?;
+            }
+            let arg = decay(self.parse_maybe_assign()?);
+            let pos = arg.loc().get_start().clone();
+            if !arg.ty().is_complete() {
+                return Err(SError::new(pos, SyntaxError::IncompleteType));
+            }
+            let arg = match func.params.get(args.len()) {
+                Some(param) if func.is_prototype => {
+                    if (param.record().is_some() || arg.ty().record().is_some())
+                        && param.unqualified() != arg.ty().unqualified()
+                    {
+                        return Err(SError::new(pos, SyntaxError::IncompatibleTypes));
+                    }
+                    self.convert(arg, &param.unqualified())?
+                }
+                None if func.is_prototype && !func.is_variadic => {
+                    return Err(SError::new(pos, SyntaxError::TooManyArguments));
+                }
+                _ => {
+                    let ty = arg.ty().promote();
+                    cast(arg, &ty)
+                }
+            };
+            args.push(arg);
+        }
+        if func.is_prototype && args.len() < func.params.len() {
+            return Err(SError::new(
+                self.last_token().get_start().clone(),
+                SyntaxError::TooFewArguments,
+            ));
+        }
+        if !func.ret.is_complete() && !func.ret.is_void() {
+            return Err(SError::new(start, SyntaxError::IncompleteType));
+        }
+        let ret_buffer = func
+            .ret
+            .record()
+            .map(|_| self.locals.declare_anonymous(func.ret.clone()).offset);
+        Ok(Expr::Call(CallExpr {
+            loc: self.finish_loc(start),
+            callee: Box::new(callee),
+            args,
+            ret_buffer,
+            ty: func.ret.clone(),
+        }))
+    }
+
     fn parse_atom(&mut self) -> SResult<Expr> {
         let token = self.cur_token();
         let tt = token.get_type().clone();
         let expr = match &tt {
-            TokenType::Name(_) => Expr::Ident(self.parse_ident()?),
+            TokenType::Name(_) => self.parse_ident()?,
             TokenType::Int32(_) => Expr::Literal(self.parse_literal(tt)?),
+            TokenType::Str(bytes) => self.parse_string(bytes)?,
             TokenType::ParenL => self.parse_paren_expr()?,
+            TokenType::VaStart | TokenType::VaArg | TokenType::VaEnd | TokenType::VaCopy => {
+                self.parse_va_builtin()?
+            }
+            TokenType::Alloca => self.parse_alloca()?,
+            TokenType::Generic => self.parse_generic()?,
             _ => self.unexpected(token)?,
         };
         Ok(expr)
     }
 
-    fn parse_ident(&mut self) -> SResult<IdentExpr> {
+    /// generic-selection = "_Generic" "(" assign ("," (type-name | "default") ":" assign)+ ")"
+    ///
+    /// the result is the association for the type of the controlling expression after
+    /// lvalue conversion, or else the `default` one. Only the types of the controlling
+    /// expression and of the other associations matter, so they are not evaluated.
+    fn parse_generic(&mut self) -> SResult<Expr> {
+        let start = self.cur_token_start();
+        self.next()?;
+        self.expect(&TokenType::ParenL)?;
+        let ty = self.parse_maybe_assign()?.ty().decay();
+        let mut types: Vec<Type> = vec![];
+        let (mut selected, mut default) = (None, None);
+        while !self.eat(&TokenType::ParenR)? {
+            self.expect(&TokenType::Comma)?;
+            let assoc_start = self.cur_token_start();
+            let assoc_ty = if self.eat(&TokenType::Default)? {
+                if default.is_some() {
+                    return Err(SError::new(assoc_start, SyntaxError::DuplicateDefault));
+                }
+                None
+            } else {
+                let assoc_ty = self.parse_type_name()?;
+                // no value has an incomplete type or a function type.
+                if !assoc_ty.is_complete() || assoc_ty.func_type().is_some() {
+                    return Err(SError::new(assoc_start, SyntaxError::InvalidType));
+                }
+                if types.iter().any(|ty| ty.is_compatible(&assoc_ty)) {
+                    return Err(SError::new(assoc_start, SyntaxError::DuplicateAssociation));
+                }
+                types.push(assoc_ty.clone());
+                Some(assoc_ty)
+            };
+            self.expect(&TokenType::Colon)?;
+            let expr = self.parse_maybe_assign()?;
+            match assoc_ty {
+                None => default = Some(expr),
+                Some(assoc_ty) if assoc_ty.is_compatible(&ty) => selected = Some(expr),
+                Some(_) => {}
+            }
+        }
+        match selected.or(default) {
+            Some(expr) => Ok(expr),
+            None => Err(SError::new(start, SyntaxError::NoGenericAssociation)),
+        }
+    }
+
+    /// alloca = "__builtin_alloca" "(" assign ")"
+    ///
+    /// the memory lives until the function returns, or until a block declaring a
+    /// variable-length array allocated before it is left.
+    fn parse_alloca(&mut self) -> SResult<Expr> {
+        let start = self.cur_token_start();
+        self.next()?;
+        self.expect(&TokenType::ParenL)?;
+        let size = self.parse_maybe_assign()?;
+        if !size.ty().is_integer() {
+            return Err(SError::new(
+                size.loc().get_start().clone(),
+                SyntaxError::IncompatibleTypes,
+            ));
+        }
+        self.expect(&TokenType::ParenR)?;
+        Ok(Expr::Alloca(AllocaExpr {
+            loc: self.finish_loc(start),
+            size: Box::new(cast(size, &Type::ulong())),
+            bottom: self.alloca_bottom(),
+            ty: Type::pointer_to(Type::void()),
+        }))
+    }
+
+    /// va-builtin = "__builtin_va_start" "(" assign "," assign ")"
+    ///            | "__builtin_va_arg" "(" assign "," type-name ")"
+    ///            | "__builtin_va_end" "(" assign ")"
+    ///            | "__builtin_va_copy" "(" assign "," assign ")"
+    fn parse_va_builtin(&mut self) -> SResult<Expr> {
+        let start = self.cur_token_start();
+        let tt = self.cur_token().get_type().clone();
+        self.next()?;
+        self.expect(&TokenType::ParenL)?;
+        let ap = self.parse_va_list()?;
+        let expr = match tt {
+            TokenType::VaStart => {
+                let area = match self.va_area {
+                    Some(area) => area,
+                    None => return Err(SError::new(start, SyntaxError::NotVariadic)),
+                };
+                // the last named parameter only tells where the variadic ones start,
+                // which is already known.
+                self.expect(&TokenType::Comma)?;
+                self.parse_maybe_assign()?;
+                self.expect(&TokenType::ParenR)?;
+                Expr::VaStart(VaStartExpr {
+                    loc: self.finish_loc(start),
+                    ap: Box::new(ap),
+                    area,
+                    ty: Type::int(),
+                })
+            }
+            TokenType::VaArg => {
+                self.expect(&TokenType::Comma)?;
+                let ty_start = self.cur_token_start();
+                let ty = self.parse_type_name()?;
+                self.expect(&TokenType::ParenR)?;
+                // only integers and pointers are passed in general purpose registers.
+                if !ty.is_integer() && !matches!(ty.kind, TypeKind::Ptr(_)) {
+                    return Err(SError::new(ty_start, SyntaxError::InvalidType));
+                }
+                Expr::VaArg(VaArgExpr {
+                    loc: self.finish_loc(start),
+                    ap: Box::new(ap),
+                    ty: ty.unqualified(),
+                })
+            }
+            // nothing needs to be released.
+            TokenType::VaEnd => {
+                self.expect(&TokenType::ParenR)?;
+                ap
+            }
+            // `va_copy(dst, src)` copies the record `*src` to `*dst`.
+            _ => {
+                self.expect(&TokenType::Comma)?;
+                let src = self.parse_va_list()?;
+                self.expect(&TokenType::ParenR)?;
+                let loc = self.finish_loc(start);
+                let dst = self.deref(ap, loc.clone())?;
+                let src = self.deref(src, loc.clone())?;
+                self.assign(dst, src, loc)?
+            }
+        };
+        Ok(expr)
+    }
+
+    /// an argument of a `va_list` builtin, the address of the record it decays to.
+    fn parse_va_list(&mut self) -> SResult<Expr> {
+        let ap = decay(self.parse_maybe_assign()?);
+        match ap.ty().base() {
+            Some(base) if base.record().is_some() => Ok(ap),
+            _ => Err(SError::new(
+                ap.loc().get_start().clone(),
+                SyntaxError::IncompatibleTypes,
+            )),
+        }
+    }
+
+    fn parse_ident(&mut self) -> SResult<Expr> {
         let start = self.cur_token_start();
         let tt = self.cur_token().get_type();
         let name = match tt {
@@ -225,13 +635,58 @@ impl State {
             _ => unreachable!(),
         };
         self.next()?;
-        let offset = self.locals.offset(&name);
+        // only a variable may be declared implicitly, by using it.
+        if self.locals.find(&name).is_none()
+            && matches!(self.cur_token().get_type(), TokenType::ParenL)
+        {
+            return Err(SError::new(start, SyntaxError::Undeclared(name)));
+        }
+        let (storage, ty) = match self.locals.offset(&name) {
+            Symbol::Var(var) => (Storage::Local(var.offset), var.ty.clone()),
+            Symbol::Global(global) => (Storage::Global(global.name.clone()), global.ty.clone()),
+            // an enumerator is an `int` constant.
+            Symbol::Enum(value) => {
+                return Ok(Expr::Literal(Lit::Int(IntLit {
+                    loc: self.finish_loc(start),
+                    num: value.to_string(),
+                    ty: Type::int(),
+                })));
+            }
+            // a type name can not be used as a value.
+            Symbol::Typedef(_) => return self.unexpected(self.last_token()),
+        };
         let expr = IdentExpr {
             loc: self.finish_loc(start),
-            name,
-            offset,
+            storage,
+            ty,
         };
-        Ok(expr)
+        Ok(Expr::Ident(expr))
+    }
+
+    /// a string literal is an anonymous `char` array with static storage duration.
+    fn parse_string(&mut self, bytes: &[u8]) -> SResult<Expr> {
+        let start = self.cur_token_start();
+        self.next()?;
+        let label = format!(".L.str.{}", self.locals.globals().len());
+        let ty = Type::array_of(Type::char(), bytes.len() + 1);
+        let mut init: Vec<Data> = bytes
+            .iter()
+            .map(|&byte| Data::Int(Type::char().truncate(byte as i64), 1))
+            .collect();
+        init.push(Data::Int(0, 1));
+        self.locals.add_global(Global {
+            name: label.clone(),
+            ty: ty.clone(),
+            is_static: true,
+            is_definition: true,
+            init: Some(init),
+            align: ty.align(),
+        });
+        Ok(Expr::Ident(IdentExpr {
+            loc: self.finish_loc(start),
+            storage: Storage::Global(label),
+            ty,
+        }))
     }
 
     fn parse_literal(&mut self, tt: TokenType) -> SResult<Lit> {
@@ -241,13 +696,208 @@ impl State {
         let literal = match tt {
             TokenType::Int32(num) => {
                 let loc = self.finish_loc(start);
-                Lit::Int32(Int32Lit { loc, num })
+                let ty = if num.parse::<i32>().is_ok() {
+                    Type::int()
+                } else {
+                    Type::long()
+                };
+                Lit::Int(IntLit { loc, num, ty })
             }
             _ => unreachable!(),
         };
         Ok(literal)
     }
 
+    /// evaluate an integer constant expression: the length of an array, a `case` label,
+    /// an enumerator, the width of a bit-field, an alignment or a `_Static_assert`.
+    pub(crate) fn eval_const(&self, expr: &Expr) -> SResult<i64> {
+        let not_constant = || SError::new(expr.loc().get_start().clone(), SyntaxError::NotConstant);
+        match expr {
+            Expr::Literal(Lit::Int(lit)) => lit.num.parse().map_err(|_| not_constant()),
+            Expr::Unary(unary) => {
+                let value = self.eval_const(&unary.argument)?;
+                match unary.op {
+                    TokenType::Plus => Ok(value),
+                    TokenType::Minus => Ok(unary.ty.truncate(value.wrapping_neg())),
+                    _ => Err(not_constant()),
+                }
+            }
+            // an address is an integer constant when it has no label, as in the `offsetof`
+            // idiom `(unsigned long)&((T *)0)->member`.
+            Expr::Cast(cast) => match self.eval_addr(&cast.argument)? {
+                (value, None) => Ok(cast.ty.truncate(value)),
+                _ => Err(not_constant()),
+            },
+            // the distance between two addresses in the same object.
+            Expr::Binary(bin) if bin.op == BinaryOp::AddrSub(BinaryAddrPos::Both) => {
+                let (left, left_label) = self.eval_addr(&bin.left)?;
+                let (right, right_label) = self.eval_addr(&bin.right)?;
+                let size = match bin.left.ty().base() {
+                    Some(base) if base.vla_size().is_some() => return Err(not_constant()),
+                    Some(base) => base.size() as i64,
+                    None => 1,
+                };
+                if left_label != right_label {
+                    return Err(not_constant());
+                }
+                left.wrapping_sub(right)
+                    .checked_div(size)
+                    .ok_or_else(not_constant)
+            }
+            Expr::Binary(bin) => {
+                let left = self.eval_const(&bin.left)?;
+                let right = self.eval_const(&bin.right)?;
+                let (l, r) = (left as u64, right as u64);
+                let unsigned = bin.left.ty().unsigned;
+                use BinaryOp::*;
+                let value = match bin.op {
+                    Add => left.wrapping_add(right),
+                    Sub => left.wrapping_sub(right),
+                    Mul => left.wrapping_mul(right),
+                    Div if unsigned => l.checked_div(r).ok_or_else(not_constant)? as i64,
+                    Div => left.checked_div(right).ok_or_else(not_constant)?,
+                    Equal => (left == right) as i64,
+                    NotEqual => (left != right) as i64,
+                    Less if unsigned => (l < r) as i64,
+                    Less => (left < right) as i64,
+                    LessEqual if unsigned => (l <= r) as i64,
+                    LessEqual => (left <= right) as i64,
+                    Great if unsigned => (l > r) as i64,
+                    Great => (left > right) as i64,
+                    GreatEqual if unsigned => (l >= r) as i64,
+                    GreatEqual => (left >= right) as i64,
+                    AddrAdd(_) | AddrSub(_) => return Err(not_constant()),
+                };
+                Ok(bin.ty.truncate(value))
+            }
+            _ => Err(not_constant()),
+        }
+    }
+
+    /// evaluate an address constant: an offset from the label of a global,
+    /// or a plain integer constant without a label.
+    pub(crate) fn eval_addr(&self, expr: &Expr) -> SResult<(i64, Option<String>)> {
+        match expr {
+            Expr::Addr(addr) => self.eval_lvalue(&addr.argument),
+            // an array decays to the address of its first element, and a function to its own.
+            Expr::Cast(cast)
+                if cast.argument.ty().is_array() || cast.argument.ty().func_type().is_some() =>
+            {
+                self.eval_lvalue(&cast.argument)
+            }
+            Expr::Cast(cast) if cast.ty.size() == 8 => match self.eval_addr(&cast.argument)? {
+                (value, None) => Ok((cast.ty.truncate(value), None)),
+                address => Ok(address),
+            },
+            Expr::Binary(bin)
+                if matches!(
+                    bin.op,
+                    BinaryOp::AddrAdd(_) | BinaryOp::AddrSub(BinaryAddrPos::Left)
+                ) =>
+            {
+                let (ptr, index) = match bin.op {
+                    BinaryOp::AddrAdd(BinaryAddrPos::Right) => (&bin.right, &bin.left),
+                    _ => (&bin.left, &bin.right),
+                };
+                let (value, label) = self.eval_addr(ptr)?;
+                let size = bin.ty.base().map_or(1, |base| base.size()) as i64;
+                let step = self.eval_const(index)?.wrapping_mul(size);
+                match bin.op {
+                    BinaryOp::AddrAdd(_) => Ok((value.wrapping_add(step), label)),
+                    _ => Ok((value.wrapping_sub(step), label)),
+                }
+            }
+            _ => Ok((self.eval_const(expr)?, None)),
+        }
+    }
+
+    /// the address of an object with static storage duration.
+    fn eval_lvalue(&self, expr: &Expr) -> SResult<(i64, Option<String>)> {
+        match expr {
+            Expr::Ident(IdentExpr {
+                storage: Storage::Global(label),
+                ..
+            }) => Ok((0, Some(label.clone()))),
+            Expr::Member(member) => {
+                let (value, label) = self.eval_lvalue(&member.base)?;
+                Ok((value + member.offset as i64, label))
+            }
+            Expr::Deref(deref) => self.eval_addr(&deref.argument),
+            _ => Err(SError::new(
+                expr.loc().get_start().clone(),
+                SyntaxError::NotConstant,
+            )),
+        }
+    }
+
+    /// `sizeof expr` or `sizeof(type)`, folded into an `unsigned long` constant.
+    /// The operand is only inspected for its type, so it is never evaluated.
+    fn parse_sizeof(&mut self) -> SResult<Expr> {
+        let start = self.cur_token_start();
+        self.next()?;
+        let ty = if self.is_paren_type_name()? {
+            self.next()?;
+            let (ty, mut sizes) = self.parse_sized_type_name()?;
+            self.expect(&TokenType::ParenR)?;
+            if matches!(self.cur_token().get_type(), TokenType::BraceL) {
+                if !sizes.is_empty() {
+                    return Err(SError::new(start, SyntaxError::InvalidType));
+                }
+                let literal = self.parse_compound_literal(ty, start.clone())?;
+                self.parse_postfix(literal, start.clone())?.ty().clone()
+            } else if ty.vla_size().is_some() && !sizes.is_empty() {
+                // the size of the array type is computed last, along with those it depends on.
+                return Ok(sizes.pop().unwrap());
+            } else {
+                ty
+            }
+        } else {
+            let expr = self.parse_maybe_unary()?;
+            expect_not_bit_field(&expr)?;
+            expr.ty().clone()
+        };
+        if !ty.is_complete() && !ty.is_void() {
+            return Err(SError::new(start, SyntaxError::IncompleteType));
+        }
+        // the size of a variable-length array is only known at runtime.
+        if let Some(size) = ty.vla_size() {
+            return Ok(Expr::Ident(hidden_local(
+                size,
+                Type::ulong(),
+                self.finish_loc(start),
+            )));
+        }
+        Ok(self.ulong_literal(ty.size(), start))
+    }
+
+    /// `_Alignof(type)`, folded into an `unsigned long` constant.
+    fn parse_alignof(&mut self) -> SResult<Expr> {
+        let start = self.cur_token_start();
+        self.next()?;
+        let ty = self.parse_paren_type_name()?;
+        Ok(self.ulong_literal(ty.align(), start))
+    }
+
+    /// the local holding the bottom of the stack allocations of the current function.
+    pub(crate) fn alloca_bottom(&mut self) -> Offset {
+        match self.alloca_bottom {
+            Some(bottom) => bottom,
+            None => {
+                let bottom = self.locals.declare_anonymous(Type::ulong()).offset;
+                self.alloca_bottom = Some(bottom);
+                bottom
+            }
+        }
+    }
+
+    fn ulong_literal(&self, value: usize, start: Pos) -> Expr {
+        Expr::Literal(Lit::Int(IntLit {
+            loc: self.finish_loc(start),
+            num: value.to_string(),
+            ty: Type::ulong(),
+        }))
+    }
+
     pub(super) fn parse_paren_expr(&mut self) -> SResult<Expr> {
         self.expect(&TokenType::ParenL)?;
         let expr = self.parse_expression()?;
@@ -256,17 +906,25 @@ impl State {
     }
 }
 
-fn contain_addr(expr: &Expr) -> bool {
-    if expr.is_addr() {
-        true
+/// structs can only be copied, not computed with, and `void` has no value.
+pub(crate) fn expect_scalar(expr: &Expr) -> SResult<()> {
+    if expr.ty().record().is_some() || expr.ty().is_void() {
+        Err(SError::new(
+            expr.loc().get_start().clone(),
+            SyntaxError::IncompatibleTypes,
+        ))
     } else {
-        expr.as_binary()
-            .map(|bin| &bin.op)
-            .and_then(|op| match op {
-                BinaryOp::AddrAdd(pos) | BinaryOp::AddrSub(pos) => Some(pos),
-                _ => None,
-            })
-            .map(|pos| matches!(pos, BinaryAddrPos::Left | BinaryAddrPos::Right))
-            .unwrap_or_default()
+        Ok(())
+    }
+}
+
+/// a bit-field has neither an address nor a size in bytes.
+pub(crate) fn expect_not_bit_field(expr: &Expr) -> SResult<()> {
+    match expr {
+        Expr::Member(member) if member.bit_field.is_some() => Err(SError::new(
+            expr.loc().get_start().clone(),
+            SyntaxError::InvalidBitField,
+        )),
+        _ => Ok(()),
     }
 }
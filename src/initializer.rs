@@ -0,0 +1,501 @@
+use crate::{
+    ast::{
+        BinaryAddrPos, BinaryExpr, BinaryOp, CompoundLitExpr, DerefExpr, Expr, IdentExpr, IntLit,
+        Lit, MemZeroExpr, MemberExpr,
+    },
+    conversion::{cast, decay},
+    error::{SError, SWarning, SyntaxError, Warning},
+    layout::BitField,
+    object::{Data, Global, Storage},
+    state::{SResult, State},
+    token::TokenType,
+    ty::{Type, TypeKind},
+    utils::{Loc, Pos},
+};
+
+/// an initializer shaped after the type of the object it initializes.
+#[derive(Debug)]
+pub(crate) struct Initializer {
+    pub ty: Type,
+    /// the value of a scalar, or of a record copied from another one.
+    /// `None` leaves the object zero.
+    pub expr: Option<Expr>,
+    /// the elements of an array, or the members of a record.
+    pub children: Vec<Initializer>,
+    /// the member of a union that is initialized, the first one unless designated.
+    pub member: usize,
+}
+
+impl Initializer {
+    fn new(ty: &Type) -> Self {
+        let children = match &ty.kind {
+            TypeKind::Array(base, len) => (0..len.unwrap_or(0))
+                .map(|_| Initializer::new(base))
+                .collect(),
+            // a flexible array member has no room in the record, so it is not initialized.
+            TypeKind::Record(record) => record
+                .borrow()
+                .members
+                .iter()
+                .filter(|member| member.ty.is_complete())
+                .map(|member| Initializer::new(&member.ty))
+                .collect(),
+            _ => vec![],
+        };
+        Self {
+            ty: ty.clone(),
+            expr: None,
+            children,
+            member: 0,
+        }
+    }
+
+    /// an array declared with `[]` grows to fit the elements it is given.
+    fn is_flexible(&self) -> bool {
+        matches!(self.ty.kind, TypeKind::Array(_, None))
+    }
+
+    fn grow(&mut self, len: usize) {
+        if let TypeKind::Array(base, None) = &self.ty.kind {
+            while self.children.len() < len {
+                self.children.push(Initializer::new(base));
+            }
+        }
+    }
+}
+
+impl State {
+    /// initializer = "{" (designation? initializer ("," designation? initializer)* ","?)? "}" | assign
+    ///
+    /// an array declared with `[]` gets its length from the initializer, in the returned `ty`.
+    pub(crate) fn parse_initializer(&mut self, ty: &Type) -> SResult<Initializer> {
+        let mut init = Initializer::new(ty);
+        if ty.record().is_some() && !matches!(self.cur_token().get_type(), TokenType::BraceL) {
+            // a record may also be initialized by copying another one.
+            let expr = self.parse_maybe_assign()?;
+            if expr.ty().unqualified() != ty.unqualified() {
+                let pos = expr.loc().get_start().clone();
+                return Err(SError::new(pos, SyntaxError::IncompatibleTypes));
+            }
+            init.expr = Some(expr);
+        } else if ty.is_array()
+            && !matches!(
+                self.cur_token().get_type(),
+                TokenType::BraceL | TokenType::Str(_)
+            )
+        {
+            return self.unexpected(self.cur_token());
+        } else {
+            self.fill_initializer(&mut init)?;
+        }
+        if let TypeKind::Array(base, None) = &ty.kind {
+            init.ty = Type::array_of(base.as_ref().clone(), init.children.len());
+        }
+        Ok(init)
+    }
+
+    fn fill_initializer(&mut self, init: &mut Initializer) -> SResult<()> {
+        if init.ty.is_array() && matches!(init.ty.base(), Some(base) if base.size() == 1) {
+            // a character array takes a string literal, optionally enclosed in braces.
+            let braced = matches!(self.cur_token().get_type(), TokenType::BraceL)
+                && matches!(self.peek_token()?.get_type(), TokenType::Str(_));
+            if braced {
+                self.next()?;
+            }
+            if let TokenType::Str(bytes) = self.cur_token().get_type() {
+                let bytes = bytes.clone();
+                let start = self.cur_token_start();
+                self.next()?;
+                string_initializer(init, &bytes, self.finish_loc(start));
+                if braced {
+                    self.eat(&TokenType::Comma)?;
+                    self.expect(&TokenType::BraceR)?;
+                }
+                return Ok(());
+            }
+        }
+        if init.ty.is_array() || init.ty.record().is_some() {
+            let braced = self.eat(&TokenType::BraceL)?;
+            return self.fill_children(init, braced, 0);
+        }
+        // a scalar may be enclosed in braces as well.
+        let braced = self.eat(&TokenType::BraceL)?;
+        let expr = self.parse_maybe_assign()?;
+        init.expr = Some(self.convert(expr, &init.ty)?);
+        if braced {
+            self.eat(&TokenType::Comma)?;
+            self.expect(&TokenType::BraceR)?;
+        }
+        Ok(())
+    }
+
+    /// initialize the elements or members in order, from the `start`th one. Without braces,
+    /// they take as many initializers of the enclosing list as they need.
+    fn fill_children(&mut self, init: &mut Initializer, braced: bool, start: usize) -> SResult<()> {
+        let is_union = init
+            .ty
+            .record()
+            .is_some_and(|record| record.borrow().is_union);
+        let len = if init.is_flexible() {
+            usize::MAX
+        } else if is_union {
+            // only one member of a union is initialized.
+            start.max(1).min(init.children.len())
+        } else {
+            init.children.len()
+        };
+        let mut i = start;
+        loop {
+            if braced {
+                if self.eat(&TokenType::BraceR)? {
+                    break;
+                }
+                if i > 0 {
+                    self.expect(&TokenType::Comma)?;
+                    if self.eat(&TokenType::BraceR)? {
+                        break;
+                    }
+                }
+                if matches!(
+                    self.cur_token().get_type(),
+                    TokenType::BracketL | TokenType::Dot
+                ) {
+                    let index = self.parse_designator(init)?;
+                    self.parse_designation(&mut init.children[index])?;
+                    i = index + 1;
+                    continue;
+                }
+            } else {
+                if i >= len || self.is_initializer_end()? {
+                    break;
+                }
+                if i > 0 {
+                    self.expect(&TokenType::Comma)?;
+                }
+            }
+            if i < len {
+                init.grow(i + 1);
+                self.fill_initializer(&mut init.children[i])?;
+            } else {
+                let pos = self.cur_token_start();
+                self.warnings
+                    .push(SWarning::new(pos, Warning::ExcessInitializer));
+                self.skip_initializer()?;
+            }
+            i += 1;
+        }
+        Ok(())
+    }
+
+    /// designation = ("[" const-expr "]" | "." ident)* "=" initializer
+    ///
+    /// `init` is what the designators parsed so far select. The initializers following a
+    /// nested designator continue with the elements or members after the designated one.
+    fn parse_designation(&mut self, init: &mut Initializer) -> SResult<()> {
+        if !matches!(
+            self.cur_token().get_type(),
+            TokenType::BracketL | TokenType::Dot
+        ) {
+            self.expect(&TokenType::Assign)?;
+            return self.fill_initializer(init);
+        }
+        let index = self.parse_designator(init)?;
+        self.parse_designation(&mut init.children[index])?;
+        self.fill_children(init, false, index + 1)
+    }
+
+    /// the index of the element or member that `[index]` or `.name` selects in `init`.
+    fn parse_designator(&mut self, init: &mut Initializer) -> SResult<usize> {
+        let start = self.cur_token_start();
+        let invalid = || SError::new(start.clone(), SyntaxError::InvalidDesignator);
+        match self.cur_token().get_type() {
+            TokenType::BracketL if init.ty.is_array() => {
+                self.next()?;
+                let expr = self.parse_expression()?;
+                let index = usize::try_from(self.eval_const(&expr)?).map_err(|_| invalid())?;
+                self.expect(&TokenType::BracketR)?;
+                init.grow(index + 1);
+                if index >= init.children.len() {
+                    return Err(invalid());
+                }
+                Ok(index)
+            }
+            TokenType::Dot if init.ty.record().is_some() => {
+                self.next()?;
+                let name_start = self.cur_token_start();
+                let name = self.parse_label()?;
+                let record = init.ty.record().unwrap().borrow();
+                let index = match record.members.iter().position(|member| member.name == name) {
+                    Some(index) if index < init.children.len() => index,
+                    Some(_) => return Err(invalid()),
+                    None => return Err(SError::new(name_start, SyntaxError::UnknownMember(name))),
+                };
+                if record.is_union {
+                    init.member = index;
+                }
+                Ok(index)
+            }
+            _ => Err(invalid()),
+        }
+    }
+
+    /// whether the enclosing initializer list ends here, with an optional trailing comma.
+    fn is_initializer_end(&mut self) -> SResult<bool> {
+        Ok(match self.cur_token().get_type() {
+            TokenType::BraceR => true,
+            TokenType::Comma => matches!(self.peek_token()?.get_type(), TokenType::BraceR),
+            _ => false,
+        })
+    }
+
+    fn skip_initializer(&mut self) -> SResult<()> {
+        if self.eat(&TokenType::BraceL)? {
+            let mut first = true;
+            while !self.eat(&TokenType::BraceR)? {
+                if !first {
+                    self.expect(&TokenType::Comma)?;
+                    if self.eat(&TokenType::BraceR)? {
+                        break;
+                    }
+                }
+                first = false;
+                self.skip_initializer()?;
+            }
+        } else {
+            self.parse_maybe_assign()?;
+        }
+        Ok(())
+    }
+
+    /// compound-literal = "(" type-name ")" initializer
+    ///
+    /// the object has static storage duration at file scope, and automatic storage duration
+    /// in a block.
+    pub(crate) fn parse_compound_literal(&mut self, ty: Type, start: Pos) -> SResult<Expr> {
+        let init = self.parse_initializer(&ty)?;
+        let ty = init.ty.clone();
+        if !ty.is_complete() {
+            return Err(SError::new(start, SyntaxError::IncompleteType));
+        }
+        let loc = self.finish_loc(start);
+        if self.locals.is_file_scope() {
+            let label = format!(".L.compound.{}", self.locals.globals().len());
+            let data = self.global_data(&init)?;
+            self.locals.add_global(Global {
+                name: label.clone(),
+                ty: ty.clone(),
+                is_static: true,
+                is_definition: true,
+                init: Some(data),
+                align: ty.align(),
+            });
+            return Ok(Expr::Ident(IdentExpr {
+                loc,
+                storage: Storage::Global(label),
+                ty,
+            }));
+        }
+        let offset = self.locals.declare_anonymous(ty.clone()).offset;
+        let var = IdentExpr {
+            loc: loc.clone(),
+            storage: Storage::Local(offset),
+            ty: ty.clone(),
+        };
+        let inits = self.local_initializer(var.clone(), init)?;
+        Ok(Expr::CompoundLit(CompoundLitExpr {
+            loc,
+            inits,
+            var,
+            ty,
+        }))
+    }
+
+    /// lay the initial value of a global out as data, zero filling what is not initialized.
+    pub(crate) fn global_data(&self, init: &Initializer) -> SResult<Vec<Data>> {
+        let mut pieces = vec![];
+        self.write_data(init, 0, &mut pieces)?;
+        // designators may initialize the elements out of order.
+        pieces.sort_by_key(|(offset, _, _)| *offset);
+        let mut data = vec![];
+        let mut offset = 0;
+        for (start, size, piece) in pieces {
+            // bit-fields sharing a byte are merged into it.
+            if start < offset {
+                if let (Some(Data::Int(byte, 1)), Data::Int(bits, 1)) = (data.last_mut(), &piece) {
+                    *byte |= bits;
+                }
+                continue;
+            }
+            if start > offset {
+                data.push(Data::Zero(start - offset));
+            }
+            data.push(piece);
+            offset = start + size;
+        }
+        if init.ty.size() > offset {
+            data.push(Data::Zero(init.ty.size() - offset));
+        }
+        Ok(data)
+    }
+
+    /// collect the `(offset, size, data)` of every initialized scalar.
+    fn write_data(
+        &self,
+        init: &Initializer,
+        offset: usize,
+        pieces: &mut Vec<(usize, usize, Data)>,
+    ) -> SResult<()> {
+        if let Some(expr) = &init.expr {
+            let size = init.ty.size();
+            let piece = match self.eval_addr(expr)? {
+                (value, None) => Data::Int(init.ty.truncate(value), size),
+                // an address only fits in a pointer sized object.
+                (addend, Some(label)) if size == 8 => Data::Addr(label, addend),
+                _ => {
+                    let pos = expr.loc().get_start().clone();
+                    return Err(SError::new(pos, SyntaxError::NotConstant));
+                }
+            };
+            pieces.push((offset, size, piece));
+            return Ok(());
+        }
+        match &init.ty.kind {
+            TypeKind::Array(base, _) => {
+                for (i, child) in init.children.iter().enumerate() {
+                    self.write_data(child, offset + i * base.size(), pieces)?;
+                }
+            }
+            TypeKind::Record(record) => {
+                let record = record.borrow();
+                let members = record.members.iter().zip(&init.children).enumerate();
+                for (index, (member, child)) in members {
+                    if record.is_union && index != init.member {
+                        continue;
+                    }
+                    match (member.bit_field, &child.expr) {
+                        (Some(bit_field), Some(expr)) => {
+                            let value = self.eval_const(expr)? as u64;
+                            write_bit_field(value, bit_field, offset + member.offset, pieces);
+                        }
+                        _ => self.write_data(child, offset + member.offset, pieces)?,
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// the expressions initializing the local `var`. A scalar is assigned, while an array or
+    /// a record is cleared first and then has each initialized element or member assigned.
+    pub(crate) fn local_initializer(
+        &mut self,
+        var: IdentExpr,
+        init: Initializer,
+    ) -> SResult<Vec<Expr>> {
+        let mut exprs = vec![];
+        if init.expr.is_none() {
+            exprs.push(Expr::MemZero(MemZeroExpr {
+                loc: var.loc.clone(),
+                ty: var.ty.clone(),
+                target: var.clone(),
+            }));
+        }
+        self.init_assignments(Expr::Ident(var), init, &mut exprs)?;
+        Ok(exprs)
+    }
+
+    fn init_assignments(
+        &mut self,
+        target: Expr,
+        init: Initializer,
+        exprs: &mut Vec<Expr>,
+    ) -> SResult<()> {
+        let loc = target.loc();
+        if let Some(expr) = init.expr {
+            exprs.push(self.initialize(target, expr, loc)?);
+            return Ok(());
+        }
+        match &init.ty.kind {
+            TypeKind::Array(base, _) => {
+                for (i, child) in init.children.into_iter().enumerate() {
+                    let index = Expr::Literal(Lit::Int(IntLit {
+                        loc: loc.clone(),
+                        num: i.to_string(),
+                        ty: Type::long(),
+                    }));
+                    // `target[i]` is `*(target + i)`
+                    let ptr = Expr::Binary(BinaryExpr {
+                        loc: loc.clone(),
+                        left: Box::new(decay(target.clone())),
+                        op: BinaryOp::AddrAdd(BinaryAddrPos::Left),
+                        right: Box::new(index),
+                        ty: Type::pointer_to(base.as_ref().clone()),
+                    });
+                    let element = Expr::Deref(DerefExpr {
+                        loc: loc.clone(),
+                        argument: Box::new(ptr),
+                        ty: base.as_ref().clone(),
+                    });
+                    self.init_assignments(element, child, exprs)?;
+                }
+            }
+            TypeKind::Record(record) => {
+                let (is_union, members) = {
+                    let record = record.borrow();
+                    (record.is_union, record.members.clone())
+                };
+                let members = members.into_iter().zip(init.children).enumerate();
+                for (index, (member, child)) in members {
+                    if is_union && index != init.member {
+                        continue;
+                    }
+                    let target = Expr::Member(MemberExpr {
+                        loc: loc.clone(),
+                        base: Box::new(target.clone()),
+                        offset: member.offset,
+                        bit_field: member.bit_field,
+                        ty: member.ty,
+                    });
+                    self.init_assignments(target, child, exprs)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// the bytes holding the bits of `value` in a bit-field of the storage unit at `offset`.
+fn write_bit_field(
+    value: u64,
+    bit_field: BitField,
+    offset: usize,
+    pieces: &mut Vec<(usize, usize, Data)>,
+) {
+    let BitField {
+        offset: shift,
+        width,
+    } = bit_field;
+    // a packed bit-field may take a ninth byte.
+    let bits = ((value & (u64::MAX >> (64 - width))) as u128) << shift;
+    for byte in shift / 8..=(shift + width - 1) / 8 {
+        let piece = Data::Int((bits >> (8 * byte) & 0xff) as i64, 1);
+        pieces.push((offset + byte, 1, piece));
+    }
+}
+
+/// a character array initialized by a string literal, which is cut to the length of the array.
+fn string_initializer(init: &mut Initializer, bytes: &[u8], loc: Loc) {
+    init.grow(bytes.len() + 1);
+    let base = init.ty.base().unwrap().clone();
+    let terminated = bytes.iter().copied().chain(Some(0));
+    for (child, byte) in init.children.iter_mut().zip(terminated) {
+        let lit = Expr::Literal(Lit::Int(IntLit {
+            loc: loc.clone(),
+            num: byte.to_string(),
+            ty: Type::int(),
+        }));
+        child.expr = Some(cast(lit, &base));
+    }
+}
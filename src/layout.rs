@@ -0,0 +1,181 @@
+use std::{cell::RefCell, fmt, rc::Rc};
+
+use crate::{ty::Type, utils::align};
+
+#[derive(Clone, Debug)]
+pub struct Member {
+    pub name: String,
+    pub ty: Type,
+    /// byte offset from the start of the record. For a bit-field, that of its storage unit,
+    /// of which only the bytes holding the field are accessed.
+    pub offset: usize,
+    pub bit_field: Option<BitField>,
+}
+
+/// where the bits of a bit-field are in its storage unit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BitField {
+    /// the position of the lowest bit of the field in the storage unit.
+    pub offset: usize,
+    pub width: usize,
+}
+
+/// a member as declared, before the record is laid out.
+#[derive(Clone, Debug)]
+pub struct MemberDecl {
+    /// `None` for an unnamed bit-field, which only takes room in the layout.
+    pub name: Option<String>,
+    pub ty: Type,
+    /// the width of a bit-field.
+    pub width: Option<usize>,
+    /// the alignment given by `_Alignas` or the `aligned` attribute, which only raises
+    /// that of the type. A bit-field has none.
+    pub align: Option<usize>,
+    /// whether the member is aligned to a byte, whatever its type.
+    pub is_packed: bool,
+}
+
+impl MemberDecl {
+    pub fn new(name: &str, ty: Type) -> Self {
+        Self {
+            name: Some(name.to_string()),
+            ty,
+            width: None,
+            align: None,
+            is_packed: false,
+        }
+    }
+}
+
+/// the members and layout of a `struct` or a `union`.
+#[derive(Debug, Default)]
+pub struct Record {
+    pub tag: Option<String>,
+    /// the members of a union all start at offset 0.
+    pub is_union: bool,
+    pub members: Vec<Member>,
+    pub size: usize,
+    pub align: usize,
+    /// a record declared as `struct tag;` or referred to before its body is incomplete.
+    pub complete: bool,
+}
+
+impl Record {
+    pub fn find(&self, name: &str) -> Option<&Member> {
+        self.members.iter().find(|member| member.name == name)
+    }
+}
+
+/// a shared handle to a record, so that every use of a tag sees it completed.
+/// Records are compared by identity, as two tags are never the same type.
+#[derive(Clone)]
+pub struct RecordRef(Rc<RefCell<Record>>);
+
+impl RecordRef {
+    pub fn new(tag: Option<String>, is_union: bool) -> Self {
+        Self(Rc::new(RefCell::new(Record {
+            tag,
+            is_union,
+            align: 1,
+            ..Default::default()
+        })))
+    }
+
+    pub fn borrow(&self) -> std::cell::Ref<'_, Record> {
+        self.0.borrow()
+    }
+
+    /// lay the `members` out in declaration order, as the System V ABI does.
+    ///
+    /// A bit-field goes at the next free bit, unless it would then cross a boundary of
+    /// the storage units of its type, in which case it starts the next unit. A zero width
+    /// moves to the next unit. Unnamed bit-fields do not align the record.
+    ///
+    /// The members of a `packed` record are aligned to a byte, and its bit-fields go at
+    /// the next free bit, across the boundaries of bytes and units, with their unit at the
+    /// byte they start in. The record is at least as aligned as `align_to`.
+    pub fn complete(&self, members: Vec<MemberDecl>, is_packed: bool, align_to: Option<usize>) {
+        let mut record = self.0.borrow_mut();
+        let is_union = record.is_union;
+        // in bits, as bit-fields need not end at a byte.
+        let mut end = 0;
+        let mut max_align = 1;
+        let mut laid_out = vec![];
+        for member in members {
+            let MemberDecl {
+                name, ty, width, ..
+            } = member;
+            let is_packed = is_packed || member.is_packed;
+            let member_align =
+                if is_packed { 1 } else { ty.align() }.max(member.align.unwrap_or(1));
+            let unit = ty.size() * 8;
+            let start = if is_union { 0 } else { end };
+            let (offset, bit_field) = match width {
+                Some(0) => {
+                    end = align(end, unit);
+                    continue;
+                }
+                Some(width) => {
+                    let start = if is_packed || start / unit == (start + width - 1) / unit {
+                        start
+                    } else {
+                        align(start, unit)
+                    };
+                    end = end.max(start + width);
+                    let offset = if is_packed {
+                        start / 8
+                    } else {
+                        start / unit * ty.size()
+                    };
+                    let bit_field = BitField {
+                        offset: start - offset * 8,
+                        width,
+                    };
+                    (offset, Some(bit_field))
+                }
+                None => {
+                    let offset = align(start, member_align * 8) / 8;
+                    end = end.max((offset + ty.size()) * 8);
+                    (offset, None)
+                }
+            };
+            let name = match name {
+                Some(name) => name,
+                None => continue,
+            };
+            max_align = max_align.max(member_align);
+            laid_out.push(Member {
+                name,
+                ty,
+                offset,
+                bit_field,
+            });
+        }
+        let max_align = max_align.max(align_to.unwrap_or(1));
+        record.members = laid_out;
+        record.size = align(align(end, 8) / 8, max_align);
+        record.align = max_align;
+        record.complete = true;
+    }
+}
+
+impl PartialEq for RecordRef {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for RecordRef {}
+
+impl fmt::Debug for RecordRef {
+    // members may point back to the record itself, so only print the tag.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let record = self.borrow();
+        write!(
+            f,
+            "{} {}",
+            if record.is_union { "union" } else { "struct" },
+            record.tag.as_deref().unwrap_or("<anonymous>")
+        )
+    }
+}
@@ -1,12 +1,18 @@
+mod asm;
 mod ast;
 mod code_gen;
+mod conversion;
+mod declaration;
 mod error;
 mod expression;
+mod initializer;
+mod layout;
 mod object;
 mod p;
 mod state;
 mod statement;
 mod token;
+mod ty;
 mod utils;
 
 use state::*;
@@ -15,7 +21,10 @@ use std::env;
 fn process(input: &str) -> SResult<()> {
     let mut state = State::new(input.to_string());
     let program = state.parse()?;
-    let context = code_gen::Context::new(program.stack_size);
+    for warning in &state.warnings {
+        eprintln!("{}", warning);
+    }
+    let context = code_gen::Context::new();
     code_gen::run(&program, context);
     Ok(())
 }
@@ -25,7 +34,10 @@ fn main() -> Result<(), String> {
     if args.len() != 2 {
         Err(format!("{:?} invalid number of arguments", args))
     } else {
-        process(&args[1]).unwrap();
+        if let Err(error) = process(&args[1]) {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
         Ok(())
     }
 }
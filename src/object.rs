@@ -1,30 +1,265 @@
 use std::collections::HashMap;
 
+use crate::{ty::Type, utils::align};
+
+/// byte offset of a local from the bottom of the stack frame.
 pub type Offset = usize;
 
+#[derive(Clone, Debug)]
+pub struct Var {
+    pub offset: Offset,
+    pub ty: Type,
+}
+
+/// a variable with static storage duration, emitted into `.data` or `.bss`.
+#[derive(Clone, Debug)]
+pub struct Global {
+    /// the assembly label, which is the name itself unless the variable is a block scope `static`.
+    pub name: String,
+    pub ty: Type,
+    /// `static` variables have internal linkage and are not exported with `.globl`.
+    pub is_static: bool,
+    /// whether this translation unit defines the variable, rather than only declaring it `extern`.
+    pub is_definition: bool,
+    /// the initial value, zero-initialized into `.bss` when absent.
+    pub init: Option<Vec<Data>>,
+    /// at least the alignment of the type.
+    pub align: usize,
+}
+
+/// a piece of the initial value of a global, laid out in order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Data {
+    /// an integer of the given size in bytes.
+    Int(i64, usize),
+    /// the address of a label plus an addend, resolved by the linker.
+    Addr(String, i64),
+    /// zero bytes, such as padding and members without an initializer.
+    Zero(usize),
+}
+
+/// where the value of a variable lives.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Storage {
+    /// byte offset in the stack frame.
+    Local(Offset),
+    /// the label of a global, addressed relative to `%rip`.
+    Global(String),
+}
+
+/// what an ordinary identifier names.
+#[derive(Clone, Debug)]
+enum Ident {
+    /// an index of `Object::vars`.
+    Var(usize),
+    /// an index of `Object::globals`.
+    Global(usize),
+    /// the value of an enumerator.
+    Enum(i64),
+    Typedef(Type),
+}
+
+#[derive(Clone, Debug)]
+pub enum Symbol<'a> {
+    Var(&'a Var),
+    Global(&'a Global),
+    Enum(i64),
+    Typedef(&'a Type),
+}
+
+#[derive(Debug, Default)]
+struct Scope {
+    idents: HashMap<String, Ident>,
+    /// `struct`, `union` and `enum` tags, which live in a namespace of their own.
+    tags: HashMap<String, Type>,
+}
+
 #[derive(Debug)]
 pub struct Object {
-    content: HashMap<String, Offset>,
+    vars: Vec<Var>,
+    globals: Vec<Global>,
+    /// the file scope followed by block scopes, innermost last.
+    scopes: Vec<Scope>,
+    /// the scope implicitly declared locals go to: the parameters of the function
+    /// being defined, or the file scope for the top level statements of `main`.
+    frame_scope: usize,
+    size: usize,
 }
 
 impl Object {
     pub fn new() -> Self {
         Self {
-            content: Default::default(),
+            vars: Default::default(),
+            globals: Default::default(),
+            scopes: vec![Default::default()],
+            frame_scope: 0,
+            size: 0,
         }
     }
 
-    pub fn offset(&mut self, name: &str) -> Offset {
-        if let Some(offset) = self.content.get(name) {
-            offset.clone()
-        } else {
-            let offset = self.content.len();
-            self.content.insert(name.to_string(), offset);
-            offset
+    pub fn enter_scope(&mut self) {
+        self.scopes.push(Default::default());
+    }
+
+    pub fn leave_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    /// start the stack frame of a function definition, in a scope for its parameters.
+    /// Returns the size of the enclosing frame, which `leave_function` restores.
+    pub fn enter_function(&mut self) -> usize {
+        self.enter_scope();
+        self.frame_scope = self.scopes.len() - 1;
+        std::mem::replace(&mut self.size, 0)
+    }
+
+    /// finish the stack frame of a function definition, returning its size.
+    pub fn leave_function(&mut self, size: usize) -> usize {
+        self.leave_scope();
+        self.frame_scope = 0;
+        std::mem::replace(&mut self.size, size)
+    }
+
+    pub fn is_file_scope(&self) -> bool {
+        self.scopes.len() == 1
+    }
+
+    /// declare `name` in the innermost scope, `None` if it is already declared there.
+    /// It is aligned to `align` bytes from the bottom of the frame.
+    pub fn declare(&mut self, name: &str, ty: Type, align_to: usize) -> Option<&Var> {
+        let scope = self.scopes.last_mut().unwrap();
+        if scope.idents.contains_key(name) {
+            return None;
         }
+        let offset = align(self.size, align_to);
+        self.size = offset + ty.size();
+        scope
+            .idents
+            .insert(name.to_string(), Ident::Var(self.vars.len()));
+        self.vars.push(Var { offset, ty });
+        self.vars.last()
+    }
+
+    /// allocate a local that no name refers to, such as a compound literal.
+    pub fn declare_anonymous(&mut self, ty: Type) -> &Var {
+        let offset = align(self.size, ty.align());
+        self.size = offset + ty.size();
+        self.vars.push(Var { offset, ty });
+        self.vars.last().unwrap()
+    }
+
+    /// declare the enumerator `name`, `false` if it is already declared in the innermost scope.
+    pub fn declare_enum(&mut self, name: &str, value: i64) -> bool {
+        let scope = self.scopes.last_mut().unwrap();
+        if scope.idents.contains_key(name) {
+            return false;
+        }
+        scope.idents.insert(name.to_string(), Ident::Enum(value));
+        true
+    }
+
+    /// declare `name` as an alias of `ty`, `false` if it is already declared in the innermost scope.
+    pub fn declare_typedef(&mut self, name: &str, ty: Type) -> bool {
+        let scope = self.scopes.last_mut().unwrap();
+        if scope.idents.contains_key(name) {
+            return false;
+        }
+        scope.idents.insert(name.to_string(), Ident::Typedef(ty));
+        true
+    }
+
+    pub fn add_global(&mut self, global: Global) -> usize {
+        self.globals.push(global);
+        self.globals.len() - 1
+    }
+
+    /// the index of the global labelled `name`, which every declaration with linkage refers to.
+    pub fn find_global(&self, name: &str) -> Option<usize> {
+        self.globals.iter().position(|global| global.name == name)
+    }
+
+    pub fn global_mut(&mut self, index: usize) -> &mut Global {
+        &mut self.globals[index]
+    }
+
+    pub fn globals(&self) -> &[Global] {
+        &self.globals
+    }
+
+    /// make `name` refer to a global in the innermost scope, `false` if it is already declared there.
+    pub fn declare_global(&mut self, name: &str, index: usize) -> bool {
+        let scope = self.scopes.last_mut().unwrap();
+        if scope.idents.contains_key(name) {
+            return false;
+        }
+        scope.idents.insert(name.to_string(), Ident::Global(index));
+        true
+    }
+
+    pub fn find(&self, name: &str) -> Option<Symbol<'_>> {
+        let ident = self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.idents.get(name))?;
+        Some(self.symbol(ident))
+    }
+
+    /// look `name` up in the innermost scope only.
+    pub fn find_innermost(&self, name: &str) -> Option<Symbol<'_>> {
+        let ident = self.scopes.last().unwrap().idents.get(name)?;
+        Some(self.symbol(ident))
+    }
+
+    fn symbol<'a>(&'a self, ident: &'a Ident) -> Symbol<'a> {
+        match ident {
+            Ident::Var(index) => Symbol::Var(&self.vars[*index]),
+            Ident::Global(index) => Symbol::Global(&self.globals[*index]),
+            Ident::Enum(value) => Symbol::Enum(*value),
+            Ident::Typedef(ty) => Symbol::Typedef(ty),
+        }
+    }
+
+    /// the type `name` stands for, if it is a typedef name in scope.
+    pub fn find_typedef(&self, name: &str) -> Option<&Type> {
+        match self.find(name)? {
+            Symbol::Typedef(ty) => Some(ty),
+            _ => None,
+        }
+    }
+
+    /// look up `name`, implicitly declaring it as a `long` in the outermost scope of the frame.
+    pub fn offset(&mut self, name: &str) -> Symbol<'_> {
+        if self.find(name).is_none() {
+            let offset = align(self.size, 8);
+            self.size = offset + 8;
+            self.scopes[self.frame_scope]
+                .idents
+                .insert(name.to_string(), Ident::Var(self.vars.len()));
+            self.vars.push(Var {
+                offset,
+                ty: Type::long(),
+            });
+        }
+        self.find(name).unwrap()
+    }
+
+    pub fn declare_tag(&mut self, tag: &str, ty: Type) {
+        let scope = self.scopes.last_mut().unwrap();
+        scope.tags.insert(tag.to_string(), ty);
+    }
+
+    /// look up `tag`, and whether it was declared in the innermost scope.
+    pub fn find_tag(&self, tag: &str) -> Option<(&Type, bool)> {
+        let innermost = self.scopes.len() - 1;
+        self.scopes
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(depth, scope)| scope.tags.get(tag).map(|ty| (ty, depth == innermost)))
     }
 
     pub fn size(&self) -> usize {
-        self.content.len()
+        self.size
     }
 }
@@ -1,27 +1,27 @@
 #[macro_export]
 macro_rules! head {
-    ($offset: expr) => {
+    ($name: expr, $offset: expr) => {
         format!(
-            r#".globl main
-main:
+            r#"{}:
     push %rbp
     mov %rsp, %rbp
     sub ${}, %rsp
         "#,
-            $offset
+            $name, $offset
         )
     };
 }
 
 #[macro_export]
 macro_rules! tail {
-    () => {
+    ($name: expr) => {
         format!(
             r#"
-    .L.return:
+    .L.return.{}:
     mov %rbp, %rsp
     pop %rbp
-    ret"#
+    ret"#,
+            $name
         )
     };
 }
@@ -30,7 +30,7 @@ macro_rules! tail {
 /// push `%rax` to stack to project
 macro_rules! push {
     () => {
-        format!("push %rax")
+        "push %rax".to_string()
     };
 }
 
@@ -1,10 +1,12 @@
 use std::collections::HashMap;
 
 use crate::{
-    ast::Program,
-    error::{SError, SyntaxError},
-    object::Object,
+    ast::{Expr, Function, Program},
+    error::{SError, SWarning, SyntaxError},
+    object::{Object, Offset},
+    statement::SwitchLabels,
     token::{Token, TokenType},
+    ty::Type,
 };
 
 use super::utils::*;
@@ -13,26 +15,95 @@ pub type SResult<T> = Result<T, SError>;
 
 pub struct State {
     pub(super) pos: usize,
-    cur_line: usize,
-    line_start: usize,
+    pub(super) cur_line: usize,
+    pub(super) line_start: usize,
     pub(super) keywords: HashMap<String, TokenType>,
     pub(super) input: Vec<char>,
     pub(super) tokens: [Token; 2],
     pub(super) locals: Object,
+    /// labels of the enclosing `switch` statements, innermost last.
+    pub(super) switches: Vec<SwitchLabels>,
+    /// how many variable-length arrays are in scope at each enclosing loop and `switch`
+    /// statement, innermost last, which `break` frees the ones past.
+    pub(super) breakable: Vec<usize>,
+    /// labels defined in the current function, with the variable-length arrays in scope
+    /// where they are.
+    pub(super) labels: HashMap<String, Vec<Offset>>,
+    /// `goto` targets of the current function, checked once its body is parsed, with the
+    /// variable-length arrays in scope where they are.
+    pub(super) gotos: Vec<(String, Pos, Vec<Offset>)>,
+    /// the variable-length arrays in scope, outermost first, as the locals the bottom of the
+    /// allocations is saved to before each. No `goto` nor `case` label may jump into the
+    /// scope of one, past its allocation.
+    pub(super) vlas: Vec<Offset>,
+    /// the return type of the current function.
+    pub(super) ret_ty: Type,
+    /// the register save area of the current function, if it is variadic.
+    pub(super) va_area: Option<Offset>,
+    /// the bottom of the stack allocations of the current function, once it makes one.
+    pub(super) alloca_bottom: Option<Offset>,
+    /// where the innermost block saves the bottom of the stack allocations, once it
+    /// declares a variable-length array, which is before the first one.
+    pub(super) saved_bottom: Option<Offset>,
+    /// the computations of the sizes of the variable-length arrays in the declarator
+    /// being parsed, in order.
+    pub(super) vla_sizes: Vec<Expr>,
+    /// the function definitions parsed so far.
+    pub(super) functions: Vec<Function>,
+    pub warnings: Vec<SWarning>,
 }
 
 impl State {
     pub fn new(input: String) -> Self {
-        let keywords = HashMap::from_iter(
-            [
-                (String::from("return"), TokenType::Return),
-                (String::from("if"), TokenType::If),
-                (String::from("else"), TokenType::Else),
-                (String::from("for"), TokenType::For),
-                (String::from("while"), TokenType::While),
-            ]
-            .into_iter(),
-        );
+        let keywords = HashMap::from_iter([
+            (String::from("return"), TokenType::Return),
+            (String::from("if"), TokenType::If),
+            (String::from("else"), TokenType::Else),
+            (String::from("for"), TokenType::For),
+            (String::from("while"), TokenType::While),
+            (String::from("switch"), TokenType::Switch),
+            (String::from("case"), TokenType::Case),
+            (String::from("default"), TokenType::Default),
+            (String::from("break"), TokenType::Break),
+            (String::from("goto"), TokenType::Goto),
+            (String::from("sizeof"), TokenType::Sizeof),
+            (String::from("_Alignof"), TokenType::Alignof),
+            (String::from("_Alignas"), TokenType::Alignas),
+            (String::from("_Static_assert"), TokenType::StaticAssert),
+            (String::from("_Generic"), TokenType::Generic),
+            (String::from("void"), TokenType::Void),
+            (String::from("char"), TokenType::Char),
+            (String::from("int"), TokenType::Int),
+            (String::from("long"), TokenType::Long),
+            (String::from("short"), TokenType::Short),
+            (String::from("signed"), TokenType::Signed),
+            (String::from("unsigned"), TokenType::Unsigned),
+            (String::from("_Bool"), TokenType::Bool),
+            (String::from("struct"), TokenType::Struct),
+            (String::from("union"), TokenType::Union),
+            (String::from("enum"), TokenType::Enum),
+            (String::from("typedef"), TokenType::Typedef),
+            (String::from("static"), TokenType::Static),
+            (String::from("extern"), TokenType::Extern),
+            (String::from("const"), TokenType::Const),
+            (String::from("volatile"), TokenType::Volatile),
+            (String::from("__volatile"), TokenType::Volatile),
+            (String::from("__volatile__"), TokenType::Volatile),
+            (String::from("restrict"), TokenType::Restrict),
+            (String::from("__restrict"), TokenType::Restrict),
+            (String::from("__restrict__"), TokenType::Restrict),
+            (String::from("__builtin_va_start"), TokenType::VaStart),
+            (String::from("__builtin_va_arg"), TokenType::VaArg),
+            (String::from("__builtin_va_end"), TokenType::VaEnd),
+            (String::from("__builtin_va_copy"), TokenType::VaCopy),
+            (String::from("__builtin_alloca"), TokenType::Alloca),
+            (String::from("__attribute__"), TokenType::Attribute),
+            (String::from("asm"), TokenType::Asm),
+            (String::from("__asm"), TokenType::Asm),
+            (String::from("__asm__"), TokenType::Asm),
+        ]);
+        let mut locals = Object::new();
+        locals.declare_typedef("__builtin_va_list", Type::va_list());
         Self {
             pos: 0,
             cur_line: 1,
@@ -40,7 +111,19 @@ impl State {
             input: input.chars().collect(),
             tokens: [Token::eof(), Token::eof()],
             keywords,
-            locals: Object::new(),
+            locals,
+            switches: vec![],
+            breakable: vec![],
+            labels: Default::default(),
+            gotos: vec![],
+            vlas: vec![],
+            ret_ty: Type::int(),
+            va_area: None,
+            alloca_bottom: None,
+            saved_bottom: None,
+            vla_sizes: vec![],
+            functions: vec![],
+            warnings: vec![],
         }
     }
 
@@ -58,7 +141,7 @@ impl State {
     pub(super) fn unexpected<T>(&self, token: &Token) -> SResult<T> {
         // TODO: lines
         let err = SError::new(
-            token.get_start().pos,
+            token.get_start().clone(),
             SyntaxError::UnexpectedToken(token.clone()),
         );
         Err(err)
@@ -1,12 +1,23 @@
+use std::collections::HashMap;
+
 use crate::{
     ast::*,
+    conversion::cast,
+    error::{SError, SyntaxError},
     state::{SResult, State},
     token::TokenType,
-    utils::{Loc, Pos},
+    ty::Type,
+    utils::{align, Loc, Pos},
 };
 
-fn align(offset: usize, align: usize) -> usize {
-    (offset + align - 1) / align * align
+/// `case` and `default` labels collected while parsing the body of a `switch`.
+pub(crate) struct SwitchLabels {
+    /// the promoted type of the controlling expression, which the labels are converted to.
+    ty: Type,
+    cases: Vec<i64>,
+    has_default: bool,
+    /// how many variable-length arrays are in scope at the `switch`.
+    vlas: usize,
 }
 
 impl State {
@@ -14,22 +25,50 @@ impl State {
         let mut body = vec![];
 
         while !self.cur_token().is_eof() {
+            // declarations at file scope define globals and functions,
+            // everything else is the body of `main`.
+            if self.is_type_name(self.cur_token().get_type()) {
+                self.parse_declaration()?;
+                continue;
+            }
             let stmt = self.parse_statement()?;
             body.push(stmt);
         }
-        let stack_size = align(self.locals.size() * 8, 16);
+        let labels = self.check_gotos()?;
+        let mut functions = std::mem::take(&mut self.functions);
+        if let Some(main) = functions.iter().find(|function| function.name == "main") {
+            if !body.is_empty() {
+                return Err(SError::new(
+                    main.loc.get_start().clone(),
+                    SyntaxError::Redefined("main".to_string()),
+                ));
+            }
+        } else if !body.is_empty() {
+            functions.push(Function {
+                loc: Loc::new(start.clone(), self.cur_pos()),
+                name: "main".to_string(),
+                is_static: false,
+                params: vec![],
+                ret: Type::int(),
+                va_area: None,
+                ret_buffer: None,
+                alloca_bottom: self.alloca_bottom.take(),
+                labels,
+                body,
+                stack_size: align(self.locals.size(), 16),
+            });
+        }
         Ok(Program {
             loc: Loc::new(start, self.cur_pos()),
-            body,
-            stack_size,
+            functions,
+            globals: self.locals.globals().to_vec(),
         })
     }
 
-    // TODO: scope
     fn parse_statement(&mut self) -> SResult<Stmt> {
         let start = self.cur_token_start();
-        let tt = self.cur_token().get_type();
-        let stmt = match tt {
+        let tt = self.cur_token().get_type().clone();
+        let stmt = match &tt {
             TokenType::Return => {
                 let stmt = self.parse_return_statement()?;
                 Stmt::Return(stmt)
@@ -44,8 +83,19 @@ impl State {
                 Stmt::Empty(EmptyStmt { loc })
             }
             TokenType::If => Stmt::If(self.parse_if_statement()?),
-            TokenType::For => Stmt::For(self.parse_for_statement()?),
+            TokenType::For => Stmt::For(Box::new(self.parse_for_statement()?)),
             TokenType::While => Stmt::While(self.parse_while_statement()?),
+            TokenType::Switch => Stmt::Switch(self.parse_switch_statement()?),
+            TokenType::Case => Stmt::Case(self.parse_case_statement()?),
+            TokenType::Default => Stmt::Default(self.parse_default_statement()?),
+            TokenType::Break => Stmt::Break(self.parse_break_statement()?),
+            TokenType::Goto => Stmt::Goto(self.parse_goto_statement()?),
+            TokenType::Asm => Stmt::Asm(self.parse_asm_statement()?),
+            // a typedef name followed by `:` is still a label.
+            TokenType::Name(_) if matches!(self.peek_token()?.get_type(), &TokenType::Colon) => {
+                Stmt::Labeled(self.parse_labeled_statement()?)
+            }
+            tt if self.is_type_name(tt) => Stmt::Decl(self.parse_declaration()?),
             _ => {
                 let expr = self.parse_expression()?;
                 self.expect(&TokenType::Semi)?;
@@ -65,21 +115,194 @@ impl State {
         self.expect(&TokenType::ParenL)?;
         let test = self.parse_expression()?;
         self.expect(&TokenType::ParenR)?;
-        let body = Box::new(self.parse_statement()?);
+        let body = Box::new(self.parse_loop_body()?);
         let loc = self.finish_loc(start);
         Ok(WhileStmt { loc, test, body })
     }
 
+    fn parse_loop_body(&mut self) -> SResult<Stmt> {
+        self.breakable.push(self.vlas.len());
+        let body = self.parse_statement();
+        self.breakable.pop();
+        body
+    }
+
+    fn parse_switch_statement(&mut self) -> SResult<SwitchStmt> {
+        let start = self.cur_token_start();
+        self.next()?;
+        let test = self.parse_paren_expr()?;
+        let ty = test.ty().promote();
+        // dispatch compares the whole of `%rax`, so widen the value after promoting it.
+        let test = cast(cast(test, &ty), &Type::long());
+        self.switches.push(SwitchLabels {
+            ty,
+            cases: vec![],
+            has_default: false,
+            vlas: self.vlas.len(),
+        });
+        let body = self.parse_loop_body();
+        let labels = self.switches.pop().unwrap();
+        Ok(SwitchStmt {
+            loc: self.finish_loc(start),
+            test,
+            body: Box::new(body?),
+            cases: labels.cases,
+            has_default: labels.has_default,
+        })
+    }
+
+    fn parse_case_statement(&mut self) -> SResult<CaseStmt> {
+        let start = self.cur_token_start();
+        if self.switches.is_empty() {
+            return Err(SError::new(start, SyntaxError::CaseOutsideSwitch));
+        }
+        self.next()?;
+        let expr = self.parse_expression()?;
+        let value = self.eval_const(&expr)?;
+        self.expect(&TokenType::Colon)?;
+        let labels = self.switches.last_mut().unwrap();
+        if self.vlas.len() > labels.vlas {
+            return Err(SError::new(start, SyntaxError::JumpIntoVla));
+        }
+        let value = labels.ty.truncate(value);
+        if labels.cases.contains(&value) {
+            return Err(SError::new(start, SyntaxError::DuplicateCase(value)));
+        }
+        let index = labels.cases.len();
+        labels.cases.push(value);
+        let body = Box::new(self.parse_statement()?);
+        Ok(CaseStmt {
+            loc: self.finish_loc(start),
+            index,
+            body,
+        })
+    }
+
+    fn parse_default_statement(&mut self) -> SResult<DefaultStmt> {
+        let start = self.cur_token_start();
+        let labels = match self.switches.last_mut() {
+            Some(labels) => labels,
+            None => return Err(SError::new(start, SyntaxError::CaseOutsideSwitch)),
+        };
+        if labels.has_default {
+            return Err(SError::new(start, SyntaxError::DuplicateDefault));
+        }
+        if self.vlas.len() > labels.vlas {
+            return Err(SError::new(start, SyntaxError::JumpIntoVla));
+        }
+        labels.has_default = true;
+        self.next()?;
+        self.expect(&TokenType::Colon)?;
+        let body = Box::new(self.parse_statement()?);
+        Ok(DefaultStmt {
+            loc: self.finish_loc(start),
+            body,
+        })
+    }
+
+    fn parse_labeled_statement(&mut self) -> SResult<LabeledStmt> {
+        let start = self.cur_token_start();
+        let label = self.parse_label()?;
+        if self.labels.contains_key(&label) {
+            return Err(SError::new(start, SyntaxError::DuplicateLabel(label)));
+        }
+        self.labels.insert(label.clone(), self.vlas.clone());
+        self.expect(&TokenType::Colon)?;
+        let body = Box::new(self.parse_statement()?);
+        Ok(LabeledStmt {
+            loc: self.finish_loc(start),
+            label,
+            body,
+        })
+    }
+
+    fn parse_goto_statement(&mut self) -> SResult<GotoStmt> {
+        let start = self.cur_token_start();
+        self.next()?;
+        let label_start = self.cur_token_start();
+        let label = self.parse_label()?;
+        self.expect(&TokenType::Semi)?;
+        self.gotos
+            .push((label.clone(), label_start, self.vlas.clone()));
+        Ok(GotoStmt {
+            loc: self.finish_loc(start),
+            label,
+            vlas: self.vlas.clone(),
+        })
+    }
+
+    pub(crate) fn parse_label(&mut self) -> SResult<String> {
+        match self.cur_token().get_type() {
+            TokenType::Name(name) => {
+                let name = name.to_string();
+                self.next()?;
+                Ok(name)
+            }
+            _ => self.unexpected(self.cur_token()),
+        }
+    }
+
+    /// labels are function scoped, so `goto` may jump forward to a label defined later.
+    /// Every variable-length array in scope at the label must be in scope at the `goto`.
+    ///
+    /// Returns how many variable-length arrays are in scope at each label.
+    pub(crate) fn check_gotos(&mut self) -> SResult<HashMap<String, usize>> {
+        for (label, start, vlas) in self.gotos.drain(..) {
+            match self.labels.get(&label) {
+                None => return Err(SError::new(start, SyntaxError::UndefinedLabel(label))),
+                Some(scope) if scope.iter().any(|vla| !vlas.contains(vla)) => {
+                    return Err(SError::new(start, SyntaxError::JumpIntoVla))
+                }
+                Some(_) => {}
+            }
+        }
+        let labels = std::mem::take(&mut self.labels);
+        Ok(labels
+            .into_iter()
+            .map(|(label, vlas)| (label, vlas.len()))
+            .collect())
+    }
+
+    fn parse_break_statement(&mut self) -> SResult<BreakStmt> {
+        let start = self.cur_token_start();
+        let vlas = match self.breakable.last() {
+            Some(&vlas) => vlas,
+            None => return Err(SError::new(start, SyntaxError::BreakOutsideLoop)),
+        };
+        self.next()?;
+        self.expect(&TokenType::Semi)?;
+        Ok(BreakStmt {
+            loc: self.finish_loc(start),
+            saved_bottom: self.vlas.get(vlas).copied(),
+        })
+    }
+
     fn parse_for_statement(&mut self) -> SResult<ForStmt> {
         let start = self.cur_token_start();
         self.next()?;
         self.expect(&TokenType::ParenL)?;
-        let init = if matches!(self.cur_token().get_type(), &TokenType::Semi) {
+        self.locals.enter_scope();
+        let vlas = self.vlas.len();
+        let stmt = self.parse_for_rest(start);
+        self.vlas.truncate(vlas);
+        self.locals.leave_scope();
+        stmt
+    }
+
+    fn parse_for_rest(&mut self, start: Pos) -> SResult<ForStmt> {
+        let init = if self.eat(&TokenType::Semi)? {
             None
+        } else if self.is_type_name(self.cur_token().get_type()) {
+            Some(Box::new(Stmt::Decl(self.parse_declaration()?)))
         } else {
-            Some(self.parse_expression()?)
+            let init_start = self.cur_token_start();
+            let expr = self.parse_expression()?;
+            self.expect(&TokenType::Semi)?;
+            Some(Box::new(Stmt::Expr(ExprStmt {
+                loc: self.finish_loc(init_start),
+                expr,
+            })))
         };
-        self.expect(&TokenType::Semi)?;
         let test = if matches!(self.cur_token().get_type(), &TokenType::Semi) {
             None
         } else {
@@ -92,7 +315,7 @@ impl State {
             Some(self.parse_expression()?)
         };
         self.expect(&TokenType::ParenR)?;
-        let body = Box::new(self.parse_statement()?);
+        let body = Box::new(self.parse_loop_body()?);
         Ok(ForStmt {
             loc: self.finish_loc(start),
             init,
@@ -103,9 +326,18 @@ impl State {
     }
 
     fn parse_block(&mut self) -> SResult<BlockStmt> {
+        self.locals.enter_scope();
+        let block = self.parse_block_body();
+        self.locals.leave_scope();
+        block
+    }
+
+    pub(crate) fn parse_block_body(&mut self) -> SResult<BlockStmt> {
         let mut body = vec![];
         let start = self.cur_token_start();
         self.expect(&TokenType::BraceL)?;
+        let outer = self.saved_bottom.take();
+        let vlas = self.vlas.len();
         loop {
             let tt = self.cur_token().get_type();
             if !matches!(tt, &TokenType::BraceR) {
@@ -116,9 +348,11 @@ impl State {
             }
         }
         self.next()?;
+        self.vlas.truncate(vlas);
         Ok(BlockStmt {
             loc: self.finish_loc(start),
             body,
+            saved_bottom: std::mem::replace(&mut self.saved_bottom, outer),
         })
     }
 
@@ -152,6 +386,27 @@ impl State {
             }
         } else {
             let argument = self.parse_expression()?;
+            let ty = self.ret_ty.clone();
+            // a `void` function may only return the value of a `void` expression.
+            if ty.is_void() != argument.ty().is_void() {
+                return Err(SError::new(
+                    argument.loc().get_start().clone(),
+                    SyntaxError::IncompatibleTypes,
+                ));
+            }
+            if (ty.record().is_some() || argument.ty().record().is_some())
+                && ty != argument.ty().unqualified()
+            {
+                return Err(SError::new(
+                    argument.loc().get_start().clone(),
+                    SyntaxError::IncompatibleTypes,
+                ));
+            }
+            let argument = if ty.is_void() {
+                argument
+            } else {
+                self.convert(argument, &ty)?
+            };
             self.expect(&TokenType::Semi)?;
             ReturnStmt {
                 loc: self.finish_loc(start),
@@ -8,6 +8,8 @@ use crate::utils::Loc;
 pub enum TokenType {
     Eof,
     Int32(String),
+    /// the bytes of a string literal, escapes resolved and without the terminating NUL.
+    Str(Vec<u8>),
     Plus,
     Minus,
     Star,
@@ -16,6 +18,13 @@ pub enum TokenType {
     ParenR,
     BraceL,
     BraceR,
+    BracketL,
+    BracketR,
+    Comma,
+    Dot,
+    /// `...`
+    Ellipsis,
+    Arrow,
     Equal,
     NotEqual,
     Less,
@@ -23,6 +32,7 @@ pub enum TokenType {
     Great,
     GreatEqual,
     Semi,
+    Colon,
     Assign,
     And,
     Name(String),
@@ -32,6 +42,42 @@ pub enum TokenType {
     Return,
     For,
     While,
+    Switch,
+    Case,
+    Default,
+    Break,
+    Goto,
+    Sizeof,
+    Alignof,
+    Alignas,
+    StaticAssert,
+    Generic,
+    /// `__attribute__`, a GNU extension.
+    Attribute,
+    /// `asm`, a GNU extension.
+    Asm,
+    Void,
+    Bool,
+    Char,
+    Short,
+    Int,
+    Long,
+    Signed,
+    Unsigned,
+    Struct,
+    Union,
+    Enum,
+    Typedef,
+    Static,
+    Extern,
+    Const,
+    Volatile,
+    Restrict,
+    VaStart,
+    VaArg,
+    VaEnd,
+    VaCopy,
+    Alloca,
 }
 
 impl TokenType {
@@ -107,6 +153,14 @@ impl Token {
     }
 }
 
+/// a saved position of the lexer, used to parse a part of the input twice.
+pub(super) struct Checkpoint {
+    pos: usize,
+    cur_line: usize,
+    line_start: usize,
+    tokens: [Token; 2],
+}
+
 impl State {
     fn finish_token(&mut self, start: Pos, r#type: TokenType) -> SResult<()> {
         let end = self.cur_pos();
@@ -119,15 +173,43 @@ impl State {
         Ok(())
     }
 
+    /// the position of the lexer, to come back to with `restore`.
+    pub(super) fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            pos: self.pos,
+            cur_line: self.cur_line,
+            line_start: self.line_start,
+            tokens: self.tokens.clone(),
+        }
+    }
+
+    pub(super) fn restore(&mut self, checkpoint: Checkpoint) {
+        self.pos = checkpoint.pos;
+        self.cur_line = checkpoint.cur_line;
+        self.line_start = checkpoint.line_start;
+        self.tokens = checkpoint.tokens;
+    }
+
+    /// lex the token after the current one without consuming it.
+    pub(super) fn peek_token(&mut self) -> SResult<Token> {
+        let (pos, cur_line, line_start) = (self.pos, self.cur_line, self.line_start);
+        let cur = self.tokens[0].clone();
+        self.next_token()?;
+        let peeked = std::mem::replace(&mut self.tokens[0], cur);
+        (self.pos, self.cur_line, self.line_start) = (pos, cur_line, line_start);
+        Ok(peeked)
+    }
+
     pub(super) fn next_token(&mut self) -> SResult<()> {
-        let start = self.cur_pos();
         self.skip_space()?;
+        let start = self.cur_pos();
         if self.pos >= self.input.len() {
             self.finish_token(start, TokenType::Eof)
         } else {
             let char = self.input[self.pos];
             match char {
                 '0'..='9' => self.read_number(),
+                '"' => self.read_string(),
                 '&' => self.read_and(),
                 '+' => self.read_plus(),
                 '-' => self.read_minus(),
@@ -141,6 +223,19 @@ impl State {
                     self.pos += 1;
                     self.finish_token(start, TokenType::ParenR)
                 }
+                '[' => {
+                    self.pos += 1;
+                    self.finish_token(start, TokenType::BracketL)
+                }
+                ']' => {
+                    self.pos += 1;
+                    self.finish_token(start, TokenType::BracketR)
+                }
+                '.' => self.read_dot(),
+                ',' => {
+                    self.pos += 1;
+                    self.finish_token(start, TokenType::Comma)
+                }
                 '{' => {
                     self.pos += 1;
                     self.finish_token(start, TokenType::BraceL)
@@ -157,6 +252,10 @@ impl State {
                     self.pos += 1;
                     self.finish_token(start, TokenType::Semi)
                 }
+                ':' => {
+                    self.pos += 1;
+                    self.finish_token(start, TokenType::Colon)
+                }
                 _ => self.read_word(),
             }
         }
@@ -167,7 +266,7 @@ impl State {
         let mut num = String::new();
         while self.pos < self.input.len() {
             let char = self.input[self.pos];
-            if ('0'..='9').contains(&char) {
+            if char.is_ascii_digit() {
                 num.push(char);
                 self.pos += 1;
             } else {
@@ -178,6 +277,74 @@ impl State {
         self.finish_token(start, TokenType::Int32(num))
     }
 
+    fn read_string(&mut self) -> SResult<()> {
+        let start = self.cur_pos();
+        self.pos += 1;
+        let mut bytes = vec![];
+        loop {
+            match self.input.get(self.pos) {
+                Some('"') => break,
+                Some('\\') => {
+                    self.pos += 1;
+                    let byte = self.read_escape()?;
+                    bytes.push(byte);
+                }
+                Some(&char) => {
+                    self.pos += 1;
+                    let mut buf = [0; 4];
+                    bytes.extend_from_slice(char.encode_utf8(&mut buf).as_bytes());
+                }
+                None => return Err(SError::new(start, SyntaxError::UnexpectedChar)),
+            }
+        }
+        self.pos += 1;
+        self.finish_token(start, TokenType::Str(bytes))
+    }
+
+    /// the byte of the escape sequence after a `\`.
+    fn read_escape(&mut self) -> SResult<u8> {
+        let char = match self.input.get(self.pos) {
+            Some(&char) => char,
+            None => return Err(SError::new(self.cur_pos(), SyntaxError::UnexpectedChar)),
+        };
+        let radix = match char {
+            '0'..='7' => 8,
+            'x' => {
+                self.pos += 1;
+                16
+            }
+            _ => {
+                self.pos += 1;
+                return Ok(match char {
+                    'a' => 7,
+                    'b' => 8,
+                    't' => b'\t',
+                    'n' => b'\n',
+                    'v' => 11,
+                    'f' => 12,
+                    'r' => b'\r',
+                    'e' => 27,
+                    _ => char as u8,
+                });
+            }
+        };
+        // an octal escape has at most three digits, a hexadecimal one is as long as its digits.
+        let mut value: u32 = 0;
+        let mut digits = 0;
+        while let Some(digit) = self.input.get(self.pos).and_then(|c| c.to_digit(radix)) {
+            if radix == 8 && digits == 3 {
+                break;
+            }
+            value = value.wrapping_mul(radix).wrapping_add(digit);
+            digits += 1;
+            self.pos += 1;
+        }
+        if digits == 0 {
+            return Err(SError::new(self.cur_pos(), SyntaxError::UnexpectedChar));
+        }
+        Ok(value as u8)
+    }
+
     fn is_valid_start(char: &char) -> bool {
         (&'a'..=&'z').contains(&char) || (&'A'..=&'Z').contains(&char) || char == &'_'
     }
@@ -230,7 +397,18 @@ impl State {
             }
         }
         self.pos += 1;
-        Err(SError::new(self.pos + 1, SyntaxError::UnexpectedChar))
+        Err(SError::new(self.cur_pos(), SyntaxError::UnexpectedChar))
+    }
+
+    fn read_dot(&mut self) -> SResult<()> {
+        let start = self.cur_pos();
+        if self.input.get(self.pos + 1) == Some(&'.') && self.input.get(self.pos + 2) == Some(&'.')
+        {
+            self.pos += 3;
+            return self.finish_token(start, TokenType::Ellipsis);
+        }
+        self.pos += 1;
+        self.finish_token(start, TokenType::Dot)
     }
 
     fn read_and(&mut self) -> SResult<()> {
@@ -273,6 +451,12 @@ impl State {
     fn read_minus(&mut self) -> SResult<()> {
         let start = self.cur_pos();
         // TODO: -=, --a, a--,
+        if let Some(&char) = self.input.get(self.pos + 1) {
+            if char == '>' {
+                self.pos += 2;
+                return self.finish_token(start, TokenType::Arrow);
+            }
+        }
         self.pos += 1;
         self.finish_token(start, TokenType::Minus)
     }
@@ -292,14 +476,17 @@ impl State {
     }
 
     #[inline]
+    /// skip whitespace, counting the lines for the positions of the tokens.
     fn skip_space(&mut self) -> SResult<()> {
-        while self.pos < self.input.len() {
-            let ch = self.input[self.pos];
-            if ch == ' ' {
-                self.pos += 1;
-            } else {
+        while let Some(&ch) = self.input.get(self.pos) {
+            if !ch.is_ascii_whitespace() {
                 break;
             }
+            self.pos += 1;
+            if ch == '\n' {
+                self.cur_line += 1;
+                self.line_start = self.pos;
+            }
         }
         Ok(())
     }
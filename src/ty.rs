@@ -0,0 +1,442 @@
+use std::rc::Rc;
+
+use crate::{
+    layout::{MemberDecl, RecordRef},
+    object::Offset,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TypeKind {
+    /// `void` has no values. It is incomplete, but `sizeof` 1 as with gcc.
+    Void,
+    /// `_Bool` holds only 0 or 1, any other value is normalized on conversion.
+    Bool,
+    /// plain `char`, which is signed, and `unsigned char`.
+    Char,
+    /// `signed char`, which has the values of `char` but is a distinct type.
+    SChar,
+    Short,
+    Int,
+    Long,
+    /// `long long`, as wide as `long` but a distinct type.
+    LongLong,
+    /// enumerations are compatible with `int`.
+    Enum,
+    Ptr(Rc<Type>),
+    /// the length is `None` for an array declared with `[]`, until an initializer completes it.
+    Array(Rc<Type>, Option<usize>),
+    /// a variable-length array, whose size in bytes is computed at runtime into the
+    /// `unsigned long` local at the offset. A variable of this type holds the address
+    /// of its elements, allocated on the stack.
+    Vla(Rc<Type>, Offset),
+    Record(RecordRef),
+    Func(Rc<FuncType>),
+}
+
+#[derive(Clone, Debug)]
+pub struct FuncType {
+    pub ret: Type,
+    pub params: Vec<Type>,
+    /// names of the parameters, which only a function definition needs.
+    pub names: Vec<Option<String>>,
+    /// whether the parameters end with `...`.
+    pub is_variadic: bool,
+    /// `false` for a declarator with empty parentheses, whose arguments are not checked.
+    pub is_prototype: bool,
+}
+
+/// parameter names and top-level qualifiers do not change the type of a function,
+/// and a function declared without a prototype agrees with any parameters.
+impl PartialEq for FuncType {
+    fn eq(&self, other: &Self) -> bool {
+        if !self.is_prototype || !other.is_prototype {
+            return self.ret == other.ret;
+        }
+        self.ret == other.ret
+            && self.is_variadic == other.is_variadic
+            && self.params.len() == other.params.len()
+            && self
+                .params
+                .iter()
+                .zip(&other.params)
+                .all(|(left, right)| left.unqualified() == right.unqualified())
+    }
+}
+
+impl Eq for FuncType {}
+
+/// `const`, `volatile` and `restrict`. A `volatile` object is loaded and stored exactly
+/// as often as the source says, as code generation never removes or merges accesses.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Qualifiers {
+    pub is_const: bool,
+    pub is_volatile: bool,
+    pub is_restrict: bool,
+}
+
+impl Qualifiers {
+    pub fn union(self, other: Qualifiers) -> Qualifiers {
+        Qualifiers {
+            is_const: self.is_const || other.is_const,
+            is_volatile: self.is_volatile || other.is_volatile,
+            is_restrict: self.is_restrict || other.is_restrict,
+        }
+    }
+
+    /// whether every qualifier of `other` is also in `self`.
+    pub fn contains(self, other: Qualifiers) -> bool {
+        self.union(other) == self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Type {
+    pub kind: TypeKind,
+    size: usize,
+    align: usize,
+    pub unsigned: bool,
+    pub qual: Qualifiers,
+}
+
+impl Type {
+    const fn scalar(kind: TypeKind, size: usize, unsigned: bool) -> Self {
+        Self {
+            kind,
+            size,
+            align: size,
+            unsigned,
+            qual: Qualifiers {
+                is_const: false,
+                is_volatile: false,
+                is_restrict: false,
+            },
+        }
+    }
+
+    pub const fn void() -> Self {
+        Self::scalar(TypeKind::Void, 1, false)
+    }
+
+    pub const fn bool() -> Self {
+        Self::scalar(TypeKind::Bool, 1, true)
+    }
+
+    pub const fn char() -> Self {
+        Self::scalar(TypeKind::Char, 1, false)
+    }
+
+    pub const fn schar() -> Self {
+        Self::scalar(TypeKind::SChar, 1, false)
+    }
+
+    pub const fn short() -> Self {
+        Self::scalar(TypeKind::Short, 2, false)
+    }
+
+    pub const fn int() -> Self {
+        Self::scalar(TypeKind::Int, 4, false)
+    }
+
+    pub const fn long() -> Self {
+        Self::scalar(TypeKind::Long, 8, false)
+    }
+
+    pub const fn long_long() -> Self {
+        Self::scalar(TypeKind::LongLong, 8, false)
+    }
+
+    pub const fn enum_type() -> Self {
+        Self::scalar(TypeKind::Enum, 4, false)
+    }
+
+    /// the type of `sizeof` and `_Alignof`.
+    pub const fn ulong() -> Self {
+        Self::scalar(TypeKind::Long, 8, true)
+    }
+
+    /// the unsigned variant of an integer type.
+    pub fn to_unsigned(&self) -> Type {
+        Type {
+            unsigned: true,
+            ..self.clone()
+        }
+    }
+
+    pub fn pointer_to(base: Type) -> Self {
+        Self::scalar(TypeKind::Ptr(Rc::new(base)), 8, true)
+    }
+
+    pub fn array_of(base: Type, len: usize) -> Self {
+        Self {
+            size: base.size() * len,
+            align: base.align(),
+            unsigned: false,
+            qual: Default::default(),
+            kind: TypeKind::Array(Rc::new(base), Some(len)),
+        }
+    }
+
+    pub fn incomplete_array_of(base: Type) -> Self {
+        Self {
+            size: 0,
+            align: base.align(),
+            unsigned: false,
+            qual: Default::default(),
+            kind: TypeKind::Array(Rc::new(base), None),
+        }
+    }
+
+    pub fn vla_of(base: Type, size: Offset) -> Self {
+        Self::scalar(TypeKind::Vla(Rc::new(base), size), 8, false)
+    }
+
+    pub fn record_of(record: RecordRef) -> Self {
+        Self {
+            kind: TypeKind::Record(record),
+            size: 0,
+            align: 1,
+            unsigned: false,
+            qual: Default::default(),
+        }
+    }
+
+    /// a function designator is `sizeof` 1, as with gcc.
+    pub fn func(
+        ret: Type,
+        params: Vec<Type>,
+        names: Vec<Option<String>>,
+        is_variadic: bool,
+        is_prototype: bool,
+    ) -> Self {
+        Self::scalar(
+            TypeKind::Func(Rc::new(FuncType {
+                ret,
+                params,
+                names,
+                is_variadic,
+                is_prototype,
+            })),
+            1,
+            false,
+        )
+    }
+
+    /// `__builtin_va_list` of the System V ABI, an array of one record so that it is
+    /// passed by reference. Records are compared by identity, so it is made only once.
+    pub fn va_list() -> Self {
+        let record = RecordRef::new(Some("__va_list_tag".to_string()), false);
+        record.complete(
+            vec![
+                MemberDecl::new("gp_offset", Type::int().to_unsigned()),
+                MemberDecl::new("fp_offset", Type::int().to_unsigned()),
+                MemberDecl::new("overflow_arg_area", Type::pointer_to(Type::char())),
+                MemberDecl::new("reg_save_area", Type::pointer_to(Type::char())),
+            ],
+            false,
+            None,
+        );
+        Type::array_of(Type::record_of(record), 1)
+    }
+
+    /// the type with `qual` added. Qualifying an array qualifies its elements.
+    pub fn qualified(&self, qual: Qualifiers) -> Type {
+        match &self.kind {
+            TypeKind::Array(base, len) => Type {
+                kind: TypeKind::Array(Rc::new(base.qualified(qual)), *len),
+                ..self.clone()
+            },
+            TypeKind::Vla(base, size) => Type {
+                kind: TypeKind::Vla(Rc::new(base.qualified(qual)), *size),
+                ..self.clone()
+            },
+            _ => Type {
+                qual: self.qual.union(qual),
+                ..self.clone()
+            },
+        }
+    }
+
+    /// the type of the value of an object of this type, which is never qualified.
+    pub fn unqualified(&self) -> Type {
+        Type {
+            qual: Default::default(),
+            ..self.clone()
+        }
+    }
+
+    /// whether an object of this type can not be assigned to: it is `const`, or an array of
+    /// `const` elements, or a record with a `const` member.
+    pub fn has_const(&self) -> bool {
+        match &self.kind {
+            _ if self.qual.is_const => true,
+            TypeKind::Array(base, _) | TypeKind::Vla(base, _) => base.has_const(),
+            TypeKind::Record(record) => record
+                .borrow()
+                .members
+                .iter()
+                .any(|member| member.ty.has_const()),
+            _ => false,
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        match &self.kind {
+            TypeKind::Record(record) => record.borrow().size,
+            _ => self.size,
+        }
+    }
+
+    pub fn align(&self) -> usize {
+        match &self.kind {
+            TypeKind::Record(record) => record.borrow().align,
+            _ => self.align,
+        }
+    }
+
+    pub fn func_type(&self) -> Option<&FuncType> {
+        match &self.kind {
+            TypeKind::Func(func) => Some(func),
+            _ => None,
+        }
+    }
+
+    pub fn record(&self) -> Option<&RecordRef> {
+        match &self.kind {
+            TypeKind::Record(record) => Some(record),
+            _ => None,
+        }
+    }
+
+    /// an object of incomplete type can not be defined or accessed.
+    pub fn is_complete(&self) -> bool {
+        match &self.kind {
+            TypeKind::Record(record) => record.borrow().complete,
+            TypeKind::Array(_, len) => len.is_some(),
+            TypeKind::Void => false,
+            _ => true,
+        }
+    }
+
+    pub fn is_void(&self) -> bool {
+        self.kind == TypeKind::Void
+    }
+
+    pub fn is_integer(&self) -> bool {
+        matches!(
+            self.kind,
+            TypeKind::Bool
+                | TypeKind::Char
+                | TypeKind::SChar
+                | TypeKind::Short
+                | TypeKind::Int
+                | TypeKind::Long
+                | TypeKind::LongLong
+                | TypeKind::Enum
+        )
+    }
+
+    /// pointers, and arrays and functions which decay to them.
+    pub fn is_ptr(&self) -> bool {
+        self.base().is_some() || self.func_type().is_some()
+    }
+
+    /// the same type, but that an enumeration is compatible with `int`, and so are pointers
+    /// to them.
+    pub fn is_compatible(&self, other: &Type) -> bool {
+        match (&self.kind, &other.kind) {
+            (TypeKind::Enum, TypeKind::Int) | (TypeKind::Int, TypeKind::Enum) => {
+                self.unsigned == other.unsigned && self.qual == other.qual
+            }
+            (TypeKind::Ptr(left), TypeKind::Ptr(right)) => {
+                self.qual == other.qual && left.is_compatible(right)
+            }
+            _ => self == other,
+        }
+    }
+
+    /// integers and pointers, but not arrays and functions until they decay.
+    pub fn is_scalar(&self) -> bool {
+        self.is_integer() || matches!(self.kind, TypeKind::Ptr(_))
+    }
+
+    pub fn is_array(&self) -> bool {
+        matches!(self.kind, TypeKind::Array(..) | TypeKind::Vla(..))
+    }
+
+    /// the local holding the size of a variable-length array.
+    pub fn vla_size(&self) -> Option<Offset> {
+        match self.kind {
+            TypeKind::Vla(_, size) => Some(size),
+            _ => None,
+        }
+    }
+
+    pub fn base(&self) -> Option<&Type> {
+        match &self.kind {
+            TypeKind::Ptr(base) | TypeKind::Array(base, _) | TypeKind::Vla(base, _) => Some(base),
+            _ => None,
+        }
+    }
+
+    /// an array used as a value is a pointer to its first element,
+    /// and a function is a pointer to itself.
+    pub fn decay(&self) -> Type {
+        match &self.kind {
+            TypeKind::Array(base, _) | TypeKind::Vla(base, _) => {
+                Type::pointer_to(base.as_ref().clone())
+            }
+            TypeKind::Func(_) => Type::pointer_to(self.clone()),
+            _ => self.unqualified(),
+        }
+    }
+
+    /// the value of the integer `value` once converted to this type.
+    pub fn truncate(&self, value: i64) -> i64 {
+        if self.kind == TypeKind::Bool {
+            return (value != 0) as i64;
+        }
+        match (self.size(), self.unsigned) {
+            (1, true) => value as u8 as i64,
+            (1, false) => value as i8 as i64,
+            (2, true) => value as u16 as i64,
+            (2, false) => value as i16 as i64,
+            (4, true) => value as u32 as i64,
+            (4, false) => value as i32 as i64,
+            _ => value,
+        }
+    }
+
+    /// integer promotion: anything narrower than `int` is computed as `int`.
+    pub fn promote(&self) -> Type {
+        if self.is_integer() && self.size() < 4 {
+            Type::int()
+        } else {
+            self.unqualified()
+        }
+    }
+
+    /// the type both operands of an arithmetic operator are converted to. Of two types
+    /// as wide, `long long` ranks above `long`, and the result is unsigned if either is.
+    pub fn common(left: &Type, right: &Type) -> Type {
+        let (left, right) = (left.promote(), right.promote());
+        if left.size() != right.size() {
+            if left.size() > right.size() {
+                left
+            } else {
+                right
+            }
+        } else {
+            let unsigned = left.unsigned || right.unsigned;
+            let ty = if right.kind == TypeKind::LongLong {
+                right
+            } else {
+                left
+            };
+            if unsigned {
+                ty.to_unsigned()
+            } else {
+                ty
+            }
+        }
+    }
+}
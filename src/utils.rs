@@ -1,3 +1,5 @@
+use std::fmt;
+
 #[derive(Clone, Debug)]
 pub struct Pos {
     pub line: usize,
@@ -11,6 +13,13 @@ impl Pos {
     }
 }
 
+impl fmt::Display for Pos {
+    /// `line:column`, both counted from 1.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column + 1)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Loc {
     start: Pos,
@@ -33,3 +42,8 @@ impl Loc {
         self.end.as_ref().unwrap()
     }
 }
+
+/// round `offset` up to a multiple of `align`.
+pub fn align(offset: usize, align: usize) -> usize {
+    offset.div_ceil(align) * align
+}
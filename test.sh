@@ -85,4 +85,587 @@ assert 7 '{ x=3; y=5; *(&x+1)=7; return y; }'
 assert 7 '{ x=3; y=5; *(&y-2+1)=7; return x; }'
 assert 5 '{ x=3; return (&x+2)-&x+3; }'
 
+assert 5 '{ x=2; switch (x) { case 1: return 3; case 2: return 5; default: return 7; } }'
+assert 7 '{ x=9; switch (x) { case 1: return 3; case 2: return 5; default: return 7; } }'
+assert 0 '{ x=9; y=0; switch (x) { case 1: y=3; case 2: y=5; } return y; }'
+assert 9 '{ x=1; y=0; switch (x) { case 1: y=y+4; case 2: y=y+5; break; case 3: y=y+6; } return y; }'
+assert 6 '{ x=-3; switch (x) { case -3: return 6; case 3: return 7; } return 8; }'
+assert 8 '{ x=0; switch (x) { default: return 8; case 1: return 9; } }'
+assert 33 '{ x=3; switch (x) { case 0: return 30; case 1: return 31; case 2: return 32; case 1+2: return 33; case 4: return 34; case 6: return 36; } return 99; }'
+assert 99 '{ x=5; switch (x) { case 0: return 30; case 1: return 31; case 2: return 32; case 3: return 33; case 4: return 34; case 6: return 36; } return 99; }'
+assert 99 '{ x=-1; switch (x) { case 0: return 30; case 1: return 31; case 2: return 32; case 3: return 33; case 4: return 34; case 6: return 36; } return 99; }'
+assert 40 '{ x=4000; switch (x) { case 1: return 10; case 10: return 20; case 100: return 30; case 4000: return 40; case 50000: return 50; case 600000: return 60; case 7000000: return 70; } return 99; }'
+assert 70 '{ x=7000000; switch (x) { case 1: return 10; case 10: return 20; case 100: return 30; case 4000: return 40; case 50000: return 50; case 600000: return 60; case 7000000: return 70; } return 99; }'
+assert 99 '{ x=11; switch (x) { case 1: return 10; case 10: return 20; case 100: return 30; case 4000: return 40; case 50000: return 50; case 600000: return 60; case 7000000: return 70; } return 99; }'
+assert 12 '{ x=1; y=0; switch (x) { case 1: switch (y) { case 0: y=10; break; } y=y+2; break; case 2: y=20; } return y; }'
+assert 3 '{ i=0; while (1) { i=i+1; if (i==3) break; } return i; }'
+assert 4 '{ for (i=0; ; i=i+1) { if (i==4) break; } return i; }'
+
+assert 3 '{ i=0; goto a; a: i=i+1; b: i=i+1; c: i=i+1; return i; }'
+assert 2 '{ i=0; goto e; d: i=i+1; e: i=i+1; f: i=i+1; return i; }'
+assert 1 '{ i=0; goto i; g: i=i+1; h: i=i+1; i: i=i+1; return i; }'
+assert 10 '{ i=0; loop: if (i<10) { i=i+1; goto loop; } return i; }'
+assert 7 '{ x=1; switch (x) { case 1: goto out; } return 3; out: return 7; }'
+
+assert 4 '{ return sizeof(int); }'
+assert 1 '{ return sizeof(char); }'
+assert 8 '{ return sizeof(long); }'
+assert 8 '{ return sizeof(long int); }'
+assert 8 '{ return sizeof(char *); }'
+assert 8 '{ return sizeof(int **); }'
+assert 4 '{ return sizeof 1; }'
+assert 4 '{ return sizeof(1+2); }'
+assert 8 '{ x=1; return sizeof x; }'
+assert 8 '{ x=1; return sizeof(x+1); }'
+assert 8 '{ x=1; return sizeof &x; }'
+assert 8 '{ x=1; return sizeof(*&x); }'
+assert 4 '{ x=1; return sizeof(x<1); }'
+assert 9 '{ return sizeof(int) + sizeof -1 + 1; }'
+assert 3 '{ x=3; sizeof(x=7); return x; }'
+assert 4 '{ return _Alignof(int); }'
+assert 1 '{ return _Alignof(char); }'
+assert 8 '{ return _Alignof(long *); }'
+
+assert 1 '{ return (char)257; }'
+assert 1 '{ return (char)255 == -1; }'
+assert 0 '{ return (int)4294967296; }'
+assert 1 '{ return (long)(int)4294967297; }'
+assert 1 '{ return (char)-1 < 0; }'
+assert 1 '{ return (long)-1 == -1; }'
+assert 0 '{ return sizeof(int) > -1; }'
+assert 1 '{ return sizeof(int) > 1; }'
+assert 1 '{ return 2147483647 + 1 < 0; }'
+assert 0 '{ return (long)2147483647 + 1 < 0; }'
+assert 4 '{ return sizeof((char)1 + (char)1); }'
+assert 8 '{ return sizeof((long)1 + 1); }'
+assert 3 '{ x=259; return *(char *)&x; }'
+assert 1 '{ x=-1; *(char *)&x=7; return x == -249; }'
+assert 1 '{ x=-1; return *(int *)&x == -1; }'
+assert 255 '{ x=1; *(char *)&x=255; return (x - 254) * 255; }'
+assert 3 '{ x=5; switch ((char)x) { case 261: return 2; } return 3; }'
+assert 3 '{ x=-1; return (-x + 2) / 1; }'
+assert 0 '{ long x = 4294967296; if ((int)x) return 1; return 0; }'
+assert 0 '{ long x = 4294967296; while ((int)x) return 1; return 0; }'
+assert 0 'int f(long x) { return x; } int main() { if (f(4294967296)) return 1; return 0; }'
+
+assert 3 '{ int x=3; return x; }'
+assert 10 '{ int x=3, y=7; return x+y; }'
+assert 3 '{ int x; int *y; y=&x; *y=3; return x; }'
+assert 3 '{ int x=3; int *y=&x; int **z=&y; return **z; }'
+assert 5 '{ int x=3; { int x=5; return x; } }'
+assert 3 '{ int x=3; { int x=5; } return x; }'
+assert 45 '{ int s=0; for (int i=0; i<10; i=i+1) s=s+i; return s; }'
+assert 4 '{ int x; return sizeof(x); }'
+assert 40 '{ int a[10]; return sizeof(a); }'
+assert 10 '{ char a[10]; return sizeof(a); }'
+assert 24 '{ int a[2][3]; return sizeof(a); }'
+assert 12 '{ int a[2][3]; return sizeof(a[1]); }'
+assert 4 '{ int a[2][3]; return sizeof(a[1][2]); }'
+assert 8 '{ int a[2][3]; return sizeof(a + 1); }'
+assert 12 '{ return sizeof(int[3]); }'
+assert 3 '{ int a[2]; *a=1; *(a+1)=2; int *p=a; return *p + *(p+1); }'
+assert 5 '{ int a[3]; a[0]=2; a[1]=3; a[2]=a[0]+a[1]; return a[2]; }'
+assert 5 '{ int a[3]; a[2]=5; return 2[a]; }'
+assert 15 '{ int a[2][3]; int i=0; for (i=0; i<6; i=i+1) *(*a+i)=i; return a[0][0]+a[0][1]+a[0][2]+a[1][0]+a[1][1]+a[1][2]; }'
+assert 4 '{ int a[2][3]; a[1][1]=4; return *(*(a+1)+1); }'
+assert 3 '{ char a[4]; a[0]=1; a[1]=2; a[3]=3; return a[3]; }'
+assert 1 '{ char a[4]; char *p=a+1; return p-a; }'
+assert 3 '{ int a[4]; int *p=a+3; return p-a; }'
+assert 1 '{ int a[4]; return (long)(a+1) - (long)a == 4; }'
+assert 1 '{ char a[4]; return (long)(a+1) - (long)a == 1; }'
+assert 1 '{ int a[4]; int *p=&a[2]; return p == a+2; }'
+assert 2 '{ char c=2; char *p=&c; return *p; }'
+assert 1 '{ char a[2]; a[0]=-1; a[1]=7; return a[0] == -1; }'
+
+assert 1 '{ struct {int a; int b;} x; x.a=1; x.b=2; return x.a; }'
+assert 2 '{ struct {int a; int b;} x; x.a=1; x.b=2; return x.b; }'
+assert 1 '{ struct {char a; int b; char c;} x; x.a=1; x.b=2; x.c=3; return x.a; }'
+assert 3 '{ struct {char a; int b; char c;} x; x.b=1; x.b=2; x.c=3; return x.c; }'
+assert 12 '{ struct {char a; int b; char c;} x; return sizeof(x); }'
+assert 16 '{ struct {char a; long b;} x; return sizeof(x); }'
+assert 8 '{ struct {char a; long b;} x; return _Alignof(struct {char a; long b;}); }'
+assert 2 '{ struct {char a; char b;} x; return sizeof(x); }'
+assert 8 '{ struct {int a; char b;} x; return sizeof(x); }'
+assert 24 '{ struct {int a[3]; long b;} x; return sizeof(x); }'
+assert 8 '{ struct {int a;} x[2]; return sizeof(x); }'
+assert 6 '{ struct {int a[3];} x[2]; x[1].a[2]=6; return x[1].a[2]; }'
+assert 3 '{ struct t {int a; int b;} x; struct t y; y.b=3; return y.b; }'
+assert 8 '{ struct t {int a; int b;}; struct t y; return sizeof(y); }'
+assert 4 '{ struct t {char a[4];}; { struct t {int a;}; } struct t y; return sizeof(y); }'
+assert 2 '{ struct t {int a;}; { struct t {char a; char b;}; struct t y; return sizeof(y); } }'
+assert 7 '{ struct t {int a; int b;} x; struct t *p=&x; p->b=7; return x.b; }'
+assert 5 '{ struct t {int a; int b;} x; x.a=5; struct t *p=&x; return (*p).a; }'
+assert 3 '{ struct node {int val; struct node *next;} a; struct node b; a.next=&b; b.val=3; return a.next->val; }'
+assert 16 '{ struct node {int val; struct node *next;} a; return sizeof(a); }'
+assert 16 '{ struct node *p; struct node {int val; struct node *next;} a; p=&a; return sizeof(*p); }'
+assert 3 '{ struct t {int a; char b; long c;} x; struct t y; x.a=1; x.b=2; x.c=-3; y=x; return y.a + y.b - y.c - 3; }'
+assert 6 '{ struct t {char c[7];} x; struct t y; x.c[6]=6; y=x; return y.c[6]; }'
+assert 4 '{ struct t {int a; int b;} x; struct t y = x; x.b=4; y=x; return y.b; }'
+assert 10 '{ struct {struct {int a; int b;} in; int c;} x; x.in.b=10; return x.in.b; }'
+assert 8 '{ struct t {int a; int b;} x; long p = (long)&x.b - (long)&x; return p * 2; }'
+
+assert 8 '{ union { int a; char b[6]; } x; return sizeof(x); }'
+assert 4 '{ union { int a; char b[4]; } x; return _Alignof(union { int a; char b[4]; }); }'
+assert 16 '{ union { long a; char b[9]; } x; return sizeof(x); }'
+assert 3 '{ union { int a; char b[4]; } x; x.a = 515; return x.b[0]; }'
+assert 2 '{ union { int a; char b[4]; } x; x.a = 515; return x.b[1]; }'
+assert 1 '{ union { int a; char b[4]; } x; return (long)&x.a == (long)&x.b; }'
+assert 7 '{ union u { int a; long b; } x; union u y; x.b = 7; y = x; return y.a; }'
+assert 9 '{ union u { int a; long b; } x; union u *p = &x; x.b = 0; p->a = 9; return x.b; }'
+assert 12 '{ struct { int tag; union { int i; char c; } v; } x; x.tag = 1; x.v.c = 11; return x.tag + x.v.c; }'
+assert 8 '{ struct { int tag; union { int i; char c; } v; } x; return sizeof(x); }'
+
+assert 0 '{ enum { zero, one, two }; return zero; }'
+assert 1 '{ enum { zero, one, two }; return one; }'
+assert 2 '{ enum { zero, one, two }; return two; }'
+assert 5 '{ enum { five=5, six, seven }; return five; }'
+assert 6 '{ enum { five=5, six, seven }; return six; }'
+assert 0 '{ enum { zero, five=5, three=3, four }; return zero; }'
+assert 5 '{ enum { zero, five=5, three=3, four }; return five; }'
+assert 3 '{ enum { zero, five=5, three=3, four }; return three; }'
+assert 4 '{ enum { zero, five=5, three=3, four }; return four; }'
+assert 1 '{ enum { neg=-2, neg1, zero }; return (neg == -2) + (neg1 == -1) + zero == 2; }'
+assert 4 '{ enum { zero, one, two } x; return sizeof(x); }'
+assert 4 '{ enum t { zero, one, two }; enum t y; return sizeof(y); }'
+assert 7 '{ enum t { a = 3, b = a * 2 + 1 }; return b; }'
+assert 9 '{ enum { a = sizeof(long) + 1, }; return a; }'
+assert 2 '{ enum t { a, b, c }; enum t x = c; switch (x) { case a: return 0; case c: return 2; } return 1; }'
+assert 3 '{ int a = 3; { enum { a = 7 }; } return a; }'
+assert 7 '{ int a = 3; { enum { a = 7 }; return a; } }'
+assert 8 '{ enum t { a, b }; int arr[b + 1]; return sizeof(arr); }'
+
+assert 1 '{ typedef int t; t x=1; return x; }'
+assert 1 '{ typedef struct {int a;} t; t x; x.a=1; return x.a; }'
+assert 1 '{ typedef int t; { t t=1; return t; } }'
+assert 2 '{ typedef struct {int a;} t; { typedef int t; } t x; x.a=2; return x.a; }'
+assert 3 '{ typedef int t; int a=3; t *p=&a; return *p; }'
+assert 3 '{ typedef int *p; int a=3; p b=&a; return *b; }'
+assert 8 '{ typedef int t, *u; t a; u b; return sizeof(a) + sizeof(b) / 2; }'
+assert 12 '{ typedef int t[3]; t a; return sizeof(a); }'
+assert 16 '{ typedef struct s s; struct s { long a, b; }; s x; return sizeof(x); }'
+assert 8 '{ typedef long t; return sizeof(t); }'
+assert 6 '{ long t = 2; long x = 3; { typedef int t; t * x; } return t * x; }'
+assert 4 '{ typedef int t; { long t = 4; return t; } }'
+assert 5 '{ typedef int t; t: return 5; }'
+assert 2 '{ typedef enum { a, b, c } e; e x = c; return x; }'
+
+assert 0 'int x; { return x; }'
+assert 3 'int x = 3; { return x; }'
+assert 7 'int x; int y; { x = 3; y = 4; return x + y; }'
+assert 3 'int x[4]; { x[0] = 1; x[3] = 2; return x[0] + x[3]; }'
+assert 16 'int x[4]; { return sizeof(x); }'
+assert 5 'int x; int x; int x = 5; int x; { return x; }'
+assert 7 'extern int x; { return x; } int x = 7;'
+assert 4 '{ extern int g; return g; } int g = 4;'
+assert 2 'static int x = 2; { return x; }'
+assert 9 'static long x; extern long x; { x = 9; return x; }'
+assert 4 'int x = 3; { int x = 4; return x; }'
+assert 3 'int x = 3; { { int x = 4; } return x; }'
+assert 44 'char c = 300; { return c; }'
+assert 255 'long l = -1; { return l; }'
+assert 8 'long l; { return sizeof(l); }'
+assert 19 'struct p { int a; long b; } pt; { pt.b = 3; return pt.b + sizeof(pt); }'
+assert 6 'int a = 2 * 3, *p; { p = &a; return *p; }'
+assert 10 'enum { N = 10 }; int n = N; { return n; }'
+assert 13 '{ int i; int s = 0; for (i = 0; i < 3; i = i + 1) { static int n = 10; n = n + 1; s = n; } return s; }'
+assert 3 '{ int i; for (i = 0; i < 3; i = i + 1) { static int n; n = n + 1; } { static int n; return n + 3; } }'
+assert 5 'typedef int t; t x = 5; { return x; }'
+
+assert 97 '{ return "abc"[0]; }'
+assert 99 '{ return "abc"[2]; }'
+assert 0 '{ return "abc"[3]; }'
+assert 4 '{ return sizeof("abc"); }'
+assert 98 '{ char *s = "abc"; return s[1]; }'
+assert 10 '{ return "\n"[0]; }'
+assert 7 '{ return "\a"[0]; }'
+assert 0 '{ return "\0"[0]; }'
+assert 34 '{ return "\""[0]; }'
+assert 92 '{ return "\\"[0]; }'
+assert 65 '{ return "\101"[0]; }'
+assert 8 '{ return "\1010"[1] - 40; }'
+assert 255 '{ return "\xff"[0]; }'
+assert 3 '{ return sizeof("\x41\x42"); }'
+assert 1 'int a[3] = {1, 2, 3}; { return a[0]; }'
+assert 3 'int a[3] = {1, 2, 3}; { return a[2]; }'
+assert 0 'int a[3] = {1}; { return a[2]; }'
+assert 4 'int a[2][3] = {{1, 2, 3}, {4, 5, 6}}; { return a[1][0]; }'
+assert 6 'int a[2][3] = {1, 2, 3, 4, 5, 6}; { return a[1][2]; }'
+assert 5 'int a[2][3] = {{1, 2}, 4, 5}; { return a[1][1] + a[0][2]; }'
+assert 98 'char s[4] = "abc"; { return s[1]; }'
+assert 0 'char s[8] = "ab"; { return s[5]; }'
+assert 99 'char s[3] = "abcd"; { return s[2]; }'
+assert 98 'char *s = "abc"; { return s[1]; }'
+assert 3 'struct { int a; long b; char c; } x = {1, 2, 3}; { return x.c; }'
+assert 2 'struct { int a; long b; } x = {1, 2,}; { return x.b; }'
+assert 0 'struct { int a; long b; } x = {1}; { return x.b; }'
+assert 3 'struct { int a[2]; int b; } x = {1, 2, 3}; { return x.b; }'
+assert 5 'struct { int a; struct { char b; int c; } d; } x = {1, {2, 5}}; { return x.d.c; }'
+assert 4 'union { int a; char b[4]; } x = {4}; { return x.b[0]; }'
+assert 3 'int x = {3}; { return x; }'
+assert 7 'int x = 7; int *p = &x; { return *p; }'
+assert 3 'int a[4] = {1, 2, 3, 4}; int *p = &a[2]; { return *p; }'
+assert 4 'int a[4] = {1, 2, 3, 4}; int *p = a + 3; { return *p; }'
+assert 2 'int a[4] = {1, 2, 3, 4}; int *p = &a[3] - 2; { return *p; }'
+assert 20 'struct { int a; int b; } s = {10, 20}; int *p = &s.b; { return *p; }'
+assert 99 'char *s = "abc" + 2; { return *s; }'
+assert 98 'char *t[2] = {"abc", "xyz"}; { return t[0][1]; }'
+assert 122 'char *t[2] = {"abc", "xyz"}; { return t[1][2]; }'
+assert 5 'int x = 5; struct { int *p; long n; } s = {&x, 3}; { return *s.p; }'
+assert 3 'long n = sizeof(int) - 1; { return n; }'
+assert 1 'int *p = 0; { return p == 0; }'
+
+assert 1 '{ int x[3] = {1, 2, 3}; return x[0]; }'
+assert 3 '{ int x[3] = {1, 2, 3}; return x[2]; }'
+assert 2 '{ int x[2][3] = {{1, 2, 3}, {4, 5, 6}}; return x[0][1]; }'
+assert 6 '{ int x[2][3] = {{1, 2, 3}, {4, 5, 6}}; return x[1][2]; }'
+assert 0 '{ int x[3] = {}; return x[0] + x[1] + x[2]; }'
+assert 0 '{ int x[3] = {1}; return x[2]; }'
+assert 0 '{ int x[2][3] = {{1, 2}}; return x[1][0]; }'
+assert 5 '{ int x[2][3] = {1, 2, 3, 4, 5}; return x[1][1]; }'
+assert 3 '{ int x[] = {1, 2, 3}; return sizeof(x) / sizeof(x[0]); }'
+assert 16 '{ int x[][2] = {{1, 2}, {3, 4}}; return sizeof(x); }'
+assert 4 '{ char s[] = "abc"; return sizeof(s); }'
+assert 99 '{ char s[] = "abc"; return s[2]; }'
+assert 0 '{ char s[8] = "ab"; return s[5]; }'
+assert 98 '{ char s[2][4] = {"ab", "cd"}; return s[0][1]; }'
+assert 100 '{ char s[2][4] = {"ab", "cd"}; return s[1][1]; }'
+assert 0 '{ char s[2][4] = {"ab", "cd"}; return s[1][2]; }'
+assert 1 '{ struct { int a; int b; int c; } x = {1, 2, 3}; return x.a; }'
+assert 3 '{ struct { int a; int b; int c; } x = {1, 2, 3}; return x.c; }'
+assert 0 '{ struct { int a; int b; int c; } x = {1}; return x.c; }'
+assert 2 '{ struct { int a; int b; } x[2] = {{1, 2}, {3, 4}}; return x[0].b; }'
+assert 3 '{ struct { int a; int b; } x[2] = {1, 2, 3, 4}; return x[1].a; }'
+assert 5 '{ struct { char a; struct { int b; long c; } d; } x = {1, 2, 5}; return x.d.c; }'
+assert 7 '{ struct t { int a; long b; } x = {3, 4}; struct t y = x; return y.a + y.b; }'
+assert 4 '{ union { int a; char b[4]; } x = {4}; return x.b[0]; }'
+assert 2 '{ int x = {2}; return x; }'
+assert 4 '{ int i = 0; int x[4] = {i = 1, i + 1, i + 2, i + 3}; return x[3]; }'
+assert 5 '{ int x[3] = {1, 2, 3, 4}; return 5; }'
+assert 3 '{ int x[5] = {[2] = 3}; return x[2]; }'
+assert 0 '{ int x[5] = {[2] = 3}; return x[1] + x[3]; }'
+assert 5 '{ int x[5] = {1, [3] = 4, 5}; return x[4]; }'
+assert 2 '{ int x[5] = {[4] = 1, [1] = 2}; return x[1]; }'
+assert 6 '{ int x[] = {[5] = 1}; return sizeof(x) / sizeof(int); }'
+assert 4 '{ int x[][2] = {[1][1] = 4}; return x[1][1]; }'
+assert 2 '{ int x[][2] = {[1][1] = 4}; return sizeof(x) / sizeof(x[0]); }'
+assert 6 '{ int x[2][3] = {[1] = {4, 5, 6}}; return x[1][2]; }'
+assert 5 '{ int x[2][3] = {[0][1] = 4, 5}; return x[0][2]; }'
+assert 2 '{ struct { int a; int b; } x = {.b = 2, .a = 1}; return x.b; }'
+assert 1 '{ struct { int a; int b; } x = {.b = 2, .a = 1}; return x.a; }'
+assert 3 '{ struct { int a; int b; int c; } x = {.b = 2, 3}; return x.c; }'
+assert 0 '{ struct { int a; int b; int c; } x = {.b = 2, 3}; return x.a; }'
+assert 6 '{ struct { int a; struct { int b; int c; } d; } x = {.d.c = 6}; return x.d.c; }'
+assert 7 '{ struct { int a; struct { int b; int c; } d; int e; } x = {.d.b = 6, 7}; return x.d.c; }'
+assert 9 '{ struct { int a; int b; } x[2] = {[1].b = 9}; return x[1].b; }'
+assert 3 '{ struct { int a; int b; } x[2] = {[1] = {.b = 3}}; return x[1].b; }'
+assert 8 '{ union { char a; long b; } x = {.b = 8}; return x.b; }'
+assert 3 '{ union { struct { char a, b; } s; int c; } x = {.s.b = 3}; return x.s.b; }'
+assert 4 'int x[] = {1, 2, 3, 4}; { return sizeof(x) / sizeof(int); }'
+assert 3 'int x[5] = {[2] = 3}; { return x[2]; }'
+assert 0 'int x[5] = {[2] = 3}; { return x[3]; }'
+assert 2 'struct { int a; long b; } x = {.b = 2}; { return x.b; }'
+assert 12 'union { char a; long b; } x = {.b = 12}; { return x.b; }'
+assert 4 'char s[] = "abc"; { return sizeof(s); }'
+assert 3 'extern int x[]; int x[] = {1, 2, 3}; { return sizeof(x) / sizeof(int); }'
+assert 6 'int x[][2] = {[2][1] = 6}; { return x[2][1] + sizeof(x) / 8 - 3; }'
+
+assert 1 '{ return (int){1}; }'
+assert 2 '{ return ((int[]){0, 1, 2})[2]; }'
+assert 2 '{ return (int[]){0, 1, 2}[2]; }'
+assert 12 '{ return sizeof((int[]){0, 1, 2}); }'
+assert 12 '{ return sizeof (int[]){0, 1, 2}; }'
+assert 3 '{ struct p { int x; int y; }; return (struct p){1, 2}.x + (struct p){1, 2}.y; }'
+assert 2 '{ struct p { int x; int y; }; return (struct p){.y = 2}.y; }'
+assert 0 '{ struct p { int x; int y; }; return (struct p){.y = 2}.x; }'
+assert 7 '{ struct p { int x; int y; } a; a = (struct p){3, 4}; return a.x + a.y; }'
+assert 5 '{ int *p = (int[]){4, 5, 6}; return p[1]; }'
+assert 5 '{ int *p = &(int){5}; return *p; }'
+assert 9 '{ int *p = &(int){5}; *p = 9; return *p; }'
+assert 98 '{ char *s = (char[]){"abc"}; return s[1]; }'
+assert 6 '{ int i; int s = 0; for (i = 0; i < 3; i = i + 1) { int *p = (int[]){1, 2}; s = s + p[0] + p[1] - 1; p[0] = 9; } return s; }'
+assert 4 '{ typedef struct { long a; long b; } t; t x = (t){.b = 4}; return x.b; }'
+assert 3 'int *p = (int[]){1, 2, 3}; { return p[2]; }'
+assert 2 'struct p { int x; int y; } *p = &(struct p){1, 2}; { return p->y; }'
+assert 4 '{ char s[] = {"abc"}; return sizeof(s); }'
+assert 3 '{ const int x = 3; return x; }'
+assert 3 '{ int const x = 3; return x; }'
+assert 4 '{ int x; int *const p = &x; *p = 4; return x; }'
+assert 2 '{ const int a[2] = {1, 2}; return a[1]; }'
+assert 5 '{ int x = 5; const int *p = &x; return *p; }'
+assert 6 '{ int x = 5; const int *p = &x; int y = 6; p = &y; return *p; }'
+assert 3 '{ volatile int v = 2; v = v + 1; return v; }'
+assert 8 '{ int *restrict p; return sizeof(p); }'
+assert 8 '{ const volatile long *const volatile p; return sizeof(p); }'
+assert 4 '{ return sizeof(const int); }'
+assert 3 '{ typedef const int T; T x = 3; return x; }'
+assert 5 '{ const struct { int a; } s = {5}; return s.a; }'
+assert 1 '{ struct { int a; } s; const struct { int a; } *p = &s; s.a = 1; return p->a; }'
+assert 7 'const int g = 7; { return g; }'
+assert 1 '{ return sizeof(_Bool); }'
+assert 2 '{ return sizeof(short); }'
+assert 2 '{ return sizeof(short int); }'
+assert 2 '{ return sizeof(unsigned short); }'
+assert 4 '{ return sizeof(unsigned); }'
+assert 4 '{ return sizeof(signed int); }'
+assert 8 '{ return sizeof(long long); }'
+assert 8 '{ return sizeof(unsigned long long int); }'
+assert 8 '{ return sizeof(long int); }'
+assert 1 '{ return sizeof(signed char); }'
+assert 1 '{ _Bool b = 2; return b; }'
+assert 0 '{ _Bool b = 0; return b; }'
+assert 1 '{ _Bool b = 256; return b; }'
+assert 1 '{ long l = 4294967296; _Bool b = l; return b; }'
+assert 1 '{ int x; _Bool b = &x; return b; }'
+assert 1 '{ return (_Bool)-1; }'
+assert 0 '{ return (_Bool)0; }'
+assert 3 '{ _Bool b = 1; return b + b + b; }'
+assert 1 '_Bool g = 5; { return g; }'
+assert 255 '{ unsigned char c = -1; return c; }'
+assert 1 '{ unsigned char c = -1; return c == 255; }'
+assert 1 '{ signed char c = -1; return c == -1; }'
+assert 1 '{ char c = 255; return c == -1; }'
+assert 1 '{ short s = 65535; return s == -1; }'
+assert 1 '{ unsigned short s = -1; return s == 65535; }'
+assert 1 '{ short s = 32767; s = s + 1; return s == -32768; }'
+assert 1 '{ unsigned u = -1; return u > 0; }'
+assert 0 '{ int i = -1; return i > 0; }'
+assert 1 '{ unsigned u = 0; return u - 1 > 100; }'
+assert 1 '{ unsigned u = -1; long l = u; return l == 4294967295; }'
+assert 1 '{ int i = -1; long l = i; return l == -1; }'
+assert 1 '{ unsigned u = -1; return u / 2 == 2147483647; }'
+assert 1 '{ int i = -4; return i / 2 == -2; }'
+assert 1 '{ unsigned long u = -1; return u / 2 == 9223372036854775807; }'
+assert 1 '{ long long l = -1; return l < 0; }'
+assert 1 '{ unsigned long long l = -1; return l > 0; }'
+assert 1 '{ long long a = 3; long b = -5; return a + b < 0; }'
+assert 1 '{ unsigned long a = 1; long long b = -2; return a + b > 0; }'
+assert 1 '{ signed char c = 100; c = c + 100; return c < 0; }'
+assert 1 '{ signed char c = 5; char *p = &c; return *p == 5; }'
+assert 1 '{ short a[3] = {1, -2, 3}; return a[1] == -2; }'
+assert 6 '{ short a[3] = {1, 2, 3}; short *p = a; return *p + p[1] + *(p + 2); }'
+assert 2 '{ unsigned short a[2]; return &a[1] - &a[0] + sizeof(a) / 4; }'
+assert 4 '{ struct { char c; short s; } x; return sizeof(x); }'
+assert 2 '{ struct { char c; short s; } x; x.s = 2; x.c = 1; return x.s; }'
+assert 1 '{ int x = 65536 + 1; short s = x; return s; }'
+assert 1 'unsigned short g = 65537; { return g; }'
+assert 1 'static signed char g = 257; { return g; }'
+assert 3 'int add(int a, int b) { return a + b; } { return add(1, 2); }'
+assert 21 'int add6(int a, int b, int c, int d, int e, int f) { return a + b + c + d + e + f; } { return add6(1, 2, 3, 4, 5, 6); }'
+assert 36 'int add8(int a, int b, int c, int d, int e, int f, int g, int h) { return a + b + c + d + e + f + g + h; } { return add8(1, 2, 3, 4, 5, 6, 7, 8); }'
+assert 12 'int f(int a, int b, int c, int d, int e, int f, int g) { return g; } { return 1 + f(1, 2, 3, 4, 5, 6, 11); }'
+assert 55 'int fib(int n) { if (n <= 1) return n; return fib(n - 1) + fib(n - 2); } { return fib(10); }'
+assert 4 'int f(int x) { return x; } { return f(f(f(4))); }'
+assert 3 'int main() { return 3; }'
+assert 6 'int f(int x); int main() { return f(3); } int f(int x) { return x * 2; }'
+assert 2 'static int f() { return 2; } int main() { return f(); }'
+assert 1 'char c(char x) { return x; } { return c(257); }'
+assert 1 'int f(int a[]) { return a[1]; } { int a[2] = {0, 1}; return f(a); }'
+assert 10 'int f() { int x; x = 10; goto end; x = 2; end: return x; } { return f(); }'
+assert 7 'int f() { a = 7; return a; } { return f(); }'
+assert 52 'int sprintf(char *buf, char *fmt, ...); { char buf[10]; sprintf(buf, "%d", 42); return buf[0]; }'
+assert 50 'int sprintf(char *buf, char *fmt, ...); { char buf[20]; sprintf(buf, "%d%d%d%d%d%d", 2, 3, 4, 5, 6, 7); return buf[0]; }'
+assert 3 'int add(int a, int b) { return a + b; } { int (*fp)(int, int) = add; return fp(1, 2); }'
+assert 3 'int add(int a, int b) { return a + b; } { int (*fp)(int, int) = &add; return (*fp)(1, 2); }'
+assert 3 'int f(int x) { return x; } { return (*f)(3); }'
+assert 3 'int f(int x) { return x; } { return (**f)(3); }'
+assert 3 'int f(int x) { return x; } { return (&f)(3); }'
+assert 3 'int f(int x) { return x; } { int (**pp)(int); int (*p)(int) = f; pp = &p; return (**pp)(3); }'
+assert 14 'int add(int a, int b) { return a + b; } int sub(int a, int b) { return a - b; } { int (*fs[2])(int, int) = {add, sub}; return fs[0](5, 4) - fs[1](5, 4) + fs[1](9, 2) - 1; }'
+assert 3 'int one() { return 1; } int two() { return 2; } int (*fs[])() = {one, two}; { return fs[0]() + fs[1](); }'
+assert 1 'int add(int a, int b) { return a + b; } int (*g)(int, int) = add; { return g == add; }'
+assert 8 'int twice(int (*f)(int), int x) { return f(f(x)); } int dbl(int x) { return x * 2; } { return twice(dbl, 2); }'
+assert 9 'int apply(int f(int), int x) { return f(x); } int sq(int x) { return x * x; } { return apply(sq, 3); }'
+assert 4 'int four() { return 4; } int (*get())() { return four; } { return get()(); }'
+assert 9 'typedef int (*F)(int); int sq(int x) { return x * x; } F g = sq; { F f = sq; return f(3) + g(0); }'
+assert 8 '{ return sizeof(int (*)(int)); }'
+assert 1 '{ return sizeof(int (int)); }'
+assert 6 'int sum(int n, ...) { __builtin_va_list ap; __builtin_va_start(ap, n); int s = 0; for (int i = 0; i < n; i = i + 1) s = s + __builtin_va_arg(ap, int); __builtin_va_end(ap); return s; } { return sum(3, 1, 2, 3); }'
+assert 55 'int sum(int n, ...) { __builtin_va_list ap; __builtin_va_start(ap, n); int s = 0; for (int i = 0; i < n; i = i + 1) s = s + __builtin_va_arg(ap, int); __builtin_va_end(ap); return s; } { return sum(10, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10); }'
+assert 45 'long sum(int a, int b, int c, int d, int e, int f, int g, ...) { __builtin_va_list ap; __builtin_va_start(ap, g); long s = a + b + c + d + e + f + g; s = s + __builtin_va_arg(ap, long); s = s + __builtin_va_arg(ap, long); return s; } { return sum(1, 2, 3, 4, 5, 6, 7, 8, 9); }'
+assert 99 'char *f(int n, ...) { __builtin_va_list ap; __builtin_va_start(ap, n); return __builtin_va_arg(ap, char *); } { return *f(0, "c"); }'
+assert 12 'int two(int n, ...) { __builtin_va_list ap; __builtin_va_list aq; __builtin_va_start(ap, n); __builtin_va_copy(aq, ap); int x = __builtin_va_arg(ap, int); int y = __builtin_va_arg(aq, int); return x + y; } { return two(0, 6); }'
+assert 53 'int vsprintf(char *buf, char *fmt, __builtin_va_list ap); int fmt(char *buf, char *f, ...) { __builtin_va_list ap; __builtin_va_start(ap, f); int n = vsprintf(buf, f, ap); __builtin_va_end(ap); return n; } { char b[20]; fmt(b, "%d-%d", 5, 7); return b[0]; }'
+assert 3 'int vsprintf(char *buf, char *fmt, __builtin_va_list ap); int fmt(char *buf, char *f, ...) { __builtin_va_list ap; __builtin_va_start(ap, f); int n = vsprintf(buf, f, ap); __builtin_va_end(ap); return n; } { char b[20]; return fmt(b, "%d-%d", 5, 7); }'
+assert 3 'int (*p)(int, ...); int f(int n, ...) { return n; } { p = f; return p(3, 4); }'
+assert 24 '{ return sizeof(__builtin_va_list); }'
+assert 8 'int sprintf(char *buf, char *fmt, ...); { char buf[10]; return sprintf(buf, "%d%d%d%d%d%d%d%d", 1, 2, 3, 4, 5, 6, 7, 8); }'
+assert 5 'struct S { char a; }; int f(struct S s) { return s.a; } { struct S s; s.a = 5; return f(s); }'
+assert 7 'struct S { char a; short b; }; int f(struct S s) { return s.a + s.b; } { struct S s; s.a = 3; s.b = 4; return f(s); }'
+assert 6 'struct S { char a, b, c; }; int f(struct S s) { return s.a + s.b + s.c; } { struct S s = {1, 2, 3}; return f(s); }'
+assert 9 'struct S { int a, b; }; int f(struct S s) { return s.a * s.b; } { struct S s = {3, 3}; return f(s); }'
+assert 12 'struct S { int a, b, c; }; int f(int x, struct S s) { return x + s.a + s.b + s.c; } { struct S s = {3, 4, 5}; return f(0, s); }'
+assert 10 'struct S { long a, b; }; long f(struct S s, struct S t) { return s.a + s.b + t.a + t.b; } { struct S s = {1, 2}; struct S t = {3, 4}; return f(s, t); }'
+assert 15 'struct S { long a, b, c; }; long f(struct S s) { return s.a + s.b + s.c; } { struct S s = {4, 5, 6}; return f(s); }'
+assert 3 'struct S { long a, b, c; }; long f(struct S s) { s.a = 9; return s.a; } { struct S s = {3, 5, 6}; f(s); return s.a; }'
+assert 36 'struct S { long a, b; }; long f(int a, int b, int c, int d, int e, struct S s, int g) { return a + b + c + d + e + s.a + s.b + g; } { struct S s = {6, 7}; return f(1, 2, 3, 4, 5, s, 8); }'
+assert 47 'struct S { char c[20]; }; int f(int a, int b, int c, int d, int e, int x, int y, struct S s, int z) { return x + y + s.c[19] + z; } { struct S s; s.c[19] = 30; return f(0, 0, 0, 0, 0, 6, 7, s, 4); }'
+assert 3 'struct S { char a, b, c; }; struct S f() { struct S s = {1, 2, 3}; return s; } { return f().c; }'
+assert 11 'struct S { int a, b, c; }; struct S f(int x) { struct S s = {x, x + 1, x + 2}; return s; } { struct S s = f(3); return s.a + s.c + 3; }'
+assert 24 'struct S { long a, b, c, d; }; struct S f(int x) { struct S s = {x, x, x, x * 3}; return s; } { struct S s = f(6); return s.a + s.d + f(2).b - 2; }'
+assert 28 'struct S { long a, b, c; }; struct S f(long a, long b, long c, long d, long e, long g, long h) { struct S s = {a + b, c + d + e, g + h}; return s; } { struct S s = f(1, 2, 3, 4, 5, 6, 7); return s.a + s.b + s.c; }'
+assert 8 'struct S { int a, b; }; struct S f(struct S s) { s.a = s.a * 2; return s; } { struct S s = {3, 2}; return f(s).a + f(s).b; }'
+assert 21 'struct S { long a, b, c; }; struct S g(struct S s) { s.c = s.a + s.b; return s; } { struct S s = {5, 8, 0}; return g(g(s)).c + 0 * g(s).a + 8; }'
+assert 15 'struct S { int a, b; }; int sum(struct S s, int n, ...) { __builtin_va_list ap; __builtin_va_start(ap, n); int t = s.a + s.b + n; for (int i = 0; i < n; i = i + 1) t = t + __builtin_va_arg(ap, int); return t; } { struct S s = {1, 2}; return sum(s, 3, 2, 3, 4); }'
+assert 42 'struct S { long a, b, c; }; int sum(struct S s, int n, ...) { __builtin_va_list ap; __builtin_va_start(ap, n); return s.c + __builtin_va_arg(ap, int); } { struct S s = {1, 2, 40}; return sum(s, 1, 2); }'
+assert 4 'struct S { char a; int b; }; struct S f() { struct S s = {1, 3}; return s; } { struct S (*p)() = f; return p().a + p().b; }'
+assert 9 'typedef struct { int quot, rem; } div_t; div_t div(int, int); { div_t d = div(23, 8); return d.quot + d.rem; }'
+assert 5 'typedef struct { long quot, rem; } ldiv_t; ldiv_t ldiv(long, long); { return ldiv(47, 9).quot; }'
+assert 3 'int add(int a, int b); { return add(1, 2); } int add(int a, int b) { return a + b; }'
+assert 5 'int f(); int f(int x) { return x; } { return f(5); }'
+assert 7 'int seven(void) { return 7; } { return seven(); }'
+assert 4 'int g; void set(int x) { g = x; } { set(4); return g; }'
+assert 6 'int g; void set(int x) { if (x > 5) { g = x; return; } g = 0; } { set(6); return g; }'
+assert 3 'int g; void inc() { g = g + 1; } void twice() { inc(); return inc(); } { g = 1; twice(); return g; }'
+assert 1 '{ return sizeof(void); }'
+assert 8 '{ return sizeof(void *); }'
+assert 5 '{ int x = 5; void *p = &x; int *q = p; return *q; }'
+assert 2 'void *id(void *p) { return p; } { int x[2] = {1, 2}; int *q = id(x); return q[1]; }'
+assert 3 '{ int x = 3; (void)x; return x; }'
+assert 44 'long wide(long x) { return x; } { return wide(44); }'
+assert 1 '_Bool b(_Bool x) { return x; } { return b(256); }'
+assert 6 'int sum(int n, ...); int sum(int n, ...) { __builtin_va_list ap; __builtin_va_start(ap, n); int s = 0; for (int i = 0; i < n; i = i + 1) s = s + __builtin_va_arg(ap, int); return s; } { return sum(3, 1, 2, 3); }'
+assert 9 'int sq(int); { int (*p)(int) = sq; return p(3); } int sq(int x) { return x * x; }'
+assert 4 'typedef struct { int a, b; } P; P mk(int a, int b); int sum(P p) { return p.a + p.b; } { return sum(mk(1, 3)); } P mk(int a, int b) { P p = {a, b}; return p; }'
+assert 20 '{ int n = 5; int a[n]; return sizeof(a); }'
+assert 9 '{ int n = 3; int a[n]; for (int i = 0; i < n; i = i + 1) a[i] = i * i; return a[0] + a[1] + a[2] + a[1] * 4; }'
+assert 60 '{ int n = 3, m = 5; int a[n][m]; return sizeof(a); }'
+assert 20 '{ int n = 3, m = 5; int a[n][m]; return sizeof(a[1]); }'
+assert 23 '{ int n = 3, m = 5; int a[n][m]; for (int i = 0; i < n; i = i + 1) for (int j = 0; j < m; j = j + 1) a[i][j] = i * 10 + j; return a[2][3]; }'
+assert 13 '{ int n = 3, m = 5; long a[n][m]; a[1][3] = 13; long *p = &a[0][0]; return p[8]; }'
+assert 40 '{ int n = 10; return sizeof(int[n]); }'
+assert 120 '{ int n = 3; return sizeof(long[n][5]); }'
+assert 2 '{ int n = 3, m = 4; int a[n][m]; return &a[2][0] - &a[1][0] - 2; }'
+assert 1 '{ int n = 3, m = 4; int a[n][m]; int (*p)[m] = a; return p + 1 == a + 1; }'
+assert 16 '{ int m = 4; int (*p)[m]; return sizeof(*p); }'
+assert 7 '{ int n = 2; char a[n]; a[0] = 3; a[1] = 4; return a[0] + a[1]; }'
+assert 1 '{ long before = 1; int n = 100; int a[n]; long after = 2; a[0] = 5; a[99] = 6; return before; }'
+assert 3 'int sum(int *a, int n) { int s = 0; for (int i = 0; i < n; i = i + 1) s = s + a[i]; return s; } { int n = 3; int a[n]; a[0] = 1; a[1] = 1; a[2] = 1; return sum(a, n); }'
+assert 1 '{ long p; long q; for (int i = 0; i < 3; i = i + 1) { int n = 1000; char a[n]; if (i == 0) p = a; else q = a; } return p == q; }'
+assert 1 'int f(int n) { char a[n]; char b[n]; return a != b; } { return f(10); }'
+assert 55 'int fill(int n) { int a[n]; for (int i = 0; i < n; i = i + 1) a[i] = i + 1; int s = 0; for (int i = 0; i < n; i = i + 1) s = s + a[i]; return s; } { return fill(10); }'
+assert 2 'int g(int x) { return x + 1; } int main() { int n = 2; goto skip; { skip: ; int a[n]; a[1] = 3; } return g(1); }'
+assert 1 'int main() { int n = 2; switch (n) { case 1: { int a[n]; } case 2: return 1; } return 0; }'
+assert 1 'int main() { int n = 1000; int s = 0; for (int i = 0; i < 100000; i = i + 1) { while (1) { int a[n]; a[999] = i; s = a[999]; break; } } return s == 99999; }'
+assert 1 'int main() { int n = 1000; int s = 0; int i = 0; again: if (i < 100000) { int a[n]; a[999] = i; s = a[999]; i = i + 1; goto again; } return s == 99999; }'
+assert 12 'int add(int a, int b) { return a + b; } { int n = 4; int x = add(5, (int)sizeof(char[n])) + 3; return x; }'
+assert 6 'int add(int a, int b, int c) { return a + b + c; } { int n = 2; return add(1, 2, *(char *)__builtin_alloca(n) = 3); }'
+assert 42 '{ char *p = __builtin_alloca(16); p[15] = 42; return p[15]; }'
+assert 1 '{ long p = __builtin_alloca(10); long q = __builtin_alloca(10); return p - q == 16; }'
+assert 3 'typedef struct { long a, b; } S; S mk(int n) { int x[n]; S s = {n, n + 1}; return s; } { S s = mk(1); return s.a + s.b; }'
+assert 5 '{ int n = 5; typedef char T[n]; n = 100; T a; return sizeof(a); }'
+assert 4 'struct S { int a:3; int b:5; }; { return sizeof(struct S); }'
+assert 4 'struct S { char a:4; int b:8; }; { return sizeof(struct S); }'
+assert 8 'struct S { int a:30; int b:4; }; { return sizeof(struct S); }'
+assert 5 'struct S { char a; int :0; char b; }; { return sizeof(struct S); }'
+assert 4 'struct S { char a; int :0; char b; }; { struct S s; return (char *)&s.b - (char *)&s; }'
+assert 1 'struct S { int a:3; char b; }; { struct S s; return (char *)&s.b - (char *)&s; }'
+assert 1 'struct S { char a; char :4; char b; }; { return _Alignof(struct S); }'
+assert 8 'struct S { long a:40; int b:20; }; { return sizeof(struct S); }'
+assert 4 'union U { int a:3; char b:7; }; { return sizeof(union U); }'
+assert 22 'struct S { unsigned a:3; unsigned b:5; }; { struct S s; s.a = 5; s.b = 17; return s.a + s.b; }'
+assert 67 'struct S { int a:3; int b:5; }; { struct S s; s.a = 5; s.b = -3; return s.a * 10 + s.b + 100; }'
+assert 96 'struct S { int a:3; int b:5; }; { struct S s; s.a = 3; s.b = 15; s.a = s.a + 1; return s.a + 100; }'
+assert 249 'struct S { unsigned a:4; unsigned b:4; }; { struct S s; s.a = 15; s.b = 0; s.b = 9; return s.a * 16 + s.b; }'
+assert 4 'struct S { unsigned a:4; unsigned b:4; }; { struct S s; return (s.a = 20); }'
+assert 117 'struct S { char c; unsigned a:3; unsigned b:10; char d; }; { struct S s = {1, 7, 1000, 9}; return s.c + s.a + s.b + s.d - 900; }'
+assert 117 'struct S { char c; unsigned a:3; unsigned b:10; char d; }; struct S g = {1, 7, 1000, 9}; { return g.c + g.a + g.b + g.d - 900; }'
+assert 18 'struct S { int a:4; int b:4; int c:24; }; struct S g = {-1, 7, 100000}; { return (g.a == -1) + g.b + g.c / 10000; }'
+assert 13 'struct S { _Bool b:1; unsigned x:7; }; { struct S s; s.b = 5; s.x = 3; return s.b * 10 + s.x; }'
+assert 2 'struct S { long a:40; long b:24; }; { struct S s; s.a = 1099511627775; s.b = -2; return (s.a == 1099511627775) + (s.b == -2) * 2; }'
+assert 7 'struct S { unsigned long a:63; unsigned long b:1; }; { struct S s; s.a = 0; s.b = 1; s.a = s.a + 5; return s.a + s.b * 2; }'
+assert 6 'struct S { int x; unsigned a:2; }; struct S f(struct S s) { s.a = s.a + 1; return s; } { struct S s = {4, 1}; return f(s).a + f(s).x; }'
+assert 84 'struct H { unsigned ver:4; unsigned ihl:4; unsigned char tos; unsigned short len; }; { struct H h; h.ver = 4; h.ihl = 5; h.tos = 0; h.len = 20; return *(unsigned char *)&h; }'
+assert 4 'struct buf { int len; char data[]; }; { return sizeof(struct buf); }'
+assert 8 'struct buf { char c; long data[]; }; { return sizeof(struct buf); }'
+assert 33 'void *malloc(unsigned long n); struct buf { int len; char data[]; }; { struct buf *b = malloc(sizeof(struct buf) + 4); b->len = 3; b->data[0] = 10; b->data[3] = 20; return b->len + b->data[0] + b->data[3]; }'
+assert 11 'struct buf { int len; int data[]; }; { struct buf b = {7}; return b.len + sizeof(b); }'
+assert 7 'struct buf { int len; int data[]; }; struct buf g = {7}; { return g.len; }'
+assert 4 'struct buf { int len; short data[]; }; { struct buf b; return (char *)b.data - (char *)&b; }'
+assert 5 'struct __attribute__((packed)) P { char c; int x; }; { return sizeof(struct P); }'
+assert 5 'struct P { char c; int x; } __attribute__((packed)); { return sizeof(struct P); }'
+assert 1 'struct __attribute__((packed)) P { char c; int x; }; { return _Alignof(struct P); }'
+assert 9 'struct __attribute__((__packed__)) P { char c; long x; short s; }; { struct P p; return (char *)&p.s - (char *)&p; }'
+assert 6 'struct P { char c; int x __attribute__((packed)); char d; }; { return sizeof(struct P); }'
+assert 71 'struct __attribute__((packed)) H { unsigned char ver; unsigned short len; unsigned int addr; }; { return sizeof(struct H) * 10 + _Alignof(struct H); }'
+assert 3 'struct __attribute__((packed)) P { char c; int a:4; int b:12; }; { return sizeof(struct P); }'
+assert 50 'struct __attribute__((packed)) P { char c; unsigned a:4; unsigned b:12; }; { struct P p; p.c = 1; p.a = 9; p.b = 4000; return p.a + p.b / 100 + p.c; }'
+assert 243 'struct __attribute__((packed)) S { char a:4; char b:6; }; { struct S s = {0}; s.b = 63; unsigned char *p = (unsigned char *)&s; return (sizeof(s) == 2) * (p[0] + p[1]); }'
+assert 1 'struct __attribute__((packed)) U { char a; int b:20; }; { char buf[6]; for (int i = 0; i < 6; i = i + 1) buf[i] = 85; struct U *u = (struct U *)buf; u->b = -1; return (buf[4] == 85) * (u->b == -1); }'
+assert 10 'struct __attribute__((packed)) T { char a:1; long b:64; char c; }; { struct T t = {-1, -2, 7}; t.b = t.b - 1; return (t.b == -3) * (t.a == -1) * (t.c == 7) * sizeof(t); }'
+assert 43 'struct __attribute__((packed)) P { char c; int x; }; struct P f(struct P p) { p.x = p.x + 1; return p; } { struct P p = {2, 40}; p = f(p); return p.c + p.x; }'
+assert 41 'struct __attribute__((packed)) P { char c; int x; }; struct P g = {3, 300}; { return g.x - g.c; }'
+assert 16 'struct __attribute__((aligned(16))) A { char c; }; { return sizeof(struct A); }'
+assert 8 'struct A { char c; } __attribute__((aligned(8))); { return _Alignof(struct A); }'
+assert 16 'struct A { char c; int x __attribute__((aligned(8))); }; { return sizeof(struct A); }'
+assert 8 'struct A { char c; _Alignas(8) int x; }; { struct A a; return (char *)&a.x - (char *)&a; }'
+assert 16 'struct A { char c; _Alignas(long) char d; }; { return sizeof(struct A); }'
+assert 8 'struct __attribute__((packed)) P { char c; _Alignas(4) int x; }; { return sizeof(struct P); }'
+assert 1 '_Alignas(16) char buf[3]; { return (long)buf / 16 * 16 == (long)buf; }'
+assert 1 'char c; _Alignas(32) int x; { return (long)&x / 32 * 32 == (long)&x; }'
+assert 1 'char c; int x __attribute__((aligned(64))); { return (long)&x / 64 * 64 == (long)&x; }'
+assert 1 '{ char c; _Alignas(16) char d; long p = (long)&d; return p / 16 * 16 == p; }'
+assert 2 '{ char c; _Alignas(8) int x, y; return ((long)&x / 8 * 8 == (long)&x) + ((long)&y / 8 * 8 == (long)&y); }'
+assert 42 'int f(int x) __attribute__((noinline)); int f(int x) { return x * 2; } { return f(21); }'
+assert 1 '_Static_assert(sizeof(int) == 4, "int"); { return 1; }'
+assert 2 '{ _Static_assert(1, "ok"); return 2; }'
+assert 3 '_Static_assert(1); { return 3; }'
+assert 8 'struct S { char c; int x; _Static_assert(sizeof(char) == 1, "char"); }; { return sizeof(struct S); }'
+assert 8 'struct S { char c; long x; }; _Static_assert((unsigned long)&((struct S *)0)->x == 8, "offsetof"); { return (unsigned long)&((struct S *)0)->x; }'
+assert 16 'struct S { char c; short s; int x; }; { int a[(long)&((struct S *)0)->x]; return sizeof(a); }'
+assert 8 'int g[10]; _Static_assert(&g[7] - &g[2] == 5, "diff"); long d = &g[9] - &g[1]; { return d; }'
+assert 8 'enum { A = (long)&((struct { char c; long l; } *)0)->l }; { return A; }'
+assert 7 '{ switch (3) { case (long)(char *)3: return 7; } return 0; }'
+assert 1 '{ int x = 0; return _Generic(x, int: 1, long: 2, default: 3); }'
+assert 2 '{ long x = 0; return _Generic(x, int: 1, long: 2, default: 3); }'
+assert 3 '{ char x = 0; return _Generic(x, int: 1, long: 2, default: 3); }'
+assert 1 '{ return _Generic(1, long: 2, default: 3, int: 1); }'
+assert 1 '{ const int x = 0; return _Generic(x, int: 1, const int: 2); }'
+assert 1 '{ int a[3]; return _Generic(a, int *: 1, int[3]: 2, default: 3); }'
+assert 2 '{ char *s = "ab"; return _Generic(s, const char *: 1, char *: 2); }'
+assert 1 '{ const char *s = "ab"; return _Generic(s, const char *: 1, char *: 2); }'
+assert 1 'int f(int x) { return x + 1; } { return _Generic(f, int (*)(int): 1, default: 2); }'
+assert 42 'int f(int x) { return x + 1; } { return _Generic(1, int: f, default: 0)(41); }'
+assert 5 'struct S { int a; }; { struct S s = {5}; return _Generic(s, struct S: s.a, default: 0); }'
+assert 2 '{ unsigned x = 0; return _Generic(x, int: 1, unsigned: 2); }'
+assert 2 '{ short x = 0; return _Generic(x + x, short: 1, int: 2); }'
+assert 7 '{ int x = 0; _Generic(x, int: x, long: 0) = 7; return x; }'
+assert 11 '{ int x = 1; int n = _Generic(x = 5, int: 10, default: 20); return x + n; }'
+assert 1 '{ return _Generic(sizeof(int), unsigned long: 1, default: 2); }'
+assert 1 '{ char x; return _Generic(x, char: 1, signed char: 2, unsigned char: 3); }'
+assert 2 '{ signed char x; return _Generic(x, char: 1, signed char: 2, unsigned char: 3); }'
+assert 3 '{ unsigned char x; return _Generic(x, char: 1, signed char: 2, unsigned char: 3); }'
+assert 1 '{ long x; return _Generic(x, long: 1, long long: 2); }'
+assert 2 '{ long long x; return _Generic(x, long: 1, long long: 2); }'
+assert 1 'enum E { A, B }; { enum E e = B; return _Generic(e, int: 1, default: 2); }'
+assert 5 'enum E { A }; { return _Generic(1, enum E: 5, long: 2); }'
+assert 6 'enum E { A, B }; { int x = 3; enum E *p = &x; int *q = p; return *q + *p; }'
+assert 5 '{ int x; asm("mov $5, %0" : "=r"(x)); return x; }'
+assert 7 '{ int x = 3; asm("add $4, %0" : "+r"(x)); return x; }'
+assert 6 '{ int x; int y = 6; asm("mov %1, %0" : "=r"(x) : "r"(y)); return x; }'
+assert 5 '{ int x; asm("mov %1, %0\n\tadd %2, %0" : "=&r"(x) : "r"(2), "r"(3)); return x; }'
+assert 3 '{ int x; asm("mov $1, %0\n\t" "add $2, %0" : "=r"(x)); return x; }'
+assert 10 '{ int x; asm("add $1, %0" : "=r"(x) : "0"(9)); return x; }'
+assert 8 '{ int x = 1; asm("movl $8, %0" : "=m"(x)); return x; }'
+assert 4 '{ int x = 4, y; asm("mov %1, %0" : "=r"(y) : "m"(x)); return y; }'
+assert 12 '{ int x; asm("mov %1, %0" : "=r"(x) : "i"(3 * 4)); return x; }'
+assert 3 '{ int a[4] = {1, 2, 3, 4}; int x; asm("mov %c2(%1), %0" : "=r"(x) : "r"(a), "i"(8)); return x; }'
+assert 20 '{ int x, y = 20; asm("mov %[in], %[out]" : [out] "=r"(x) : [in] "r"(y)); return x; }'
+assert 7 '{ long x; int y = 7; asm("movslq %k1, %q0" : "=r"(x) : "r"(y)); return x; }'
+assert 65 '{ char c; asm("movb $65, %b0" : "=r"(c)); return c; }'
+assert 4 '{ char c = -2; asm("add $1, %0" : "+r"(c)); return c + 5; }'
+assert 9 '{ int a[2] = {0, 0}; asm("mov $9, %0" : "=r"(a[1])); return a[1]; }'
+assert 1 'struct S { char c; long l; }; { struct S s; asm("mov $-1, %0" : "=r"(s.l)); return s.l == -1; }'
+assert 12 'int g; { asm("movl $12, %0" : "=m"(g)); return g; }'
+assert 6 'int f() { return 5; } { int x; asm("lea 1(%1), %0" : "=r"(x) : "r"((long)f())); return x; }'
+assert 1 '{ unsigned lo, hi; long t0, t1; asm volatile("rdtsc" : "=a"(lo), "=d"(hi)); t0 = hi * 4294967296 + lo; asm volatile("rdtsc" : "=a"(lo), "=d"(hi)); t1 = hi * 4294967296 + lo; return t1 > t0; }'
+assert 1 '{ long ret; asm volatile("syscall" : "=a"(ret) : "a"(39) : "rcx", "r11", "memory"); return ret > 0; }'
+assert 3 'long sys_write(long fd, char *buf, long n) { long ret; __asm__ __volatile__("syscall" : "=a"(ret) : "a"(1), "D"(fd), "S"(buf), "d"(n) : "rcx", "r11", "memory"); return ret; } { return sys_write(1, "", 0) + 3; }'
+assert 4 '{ int x; asm("mov $4, %0" : "=b"(x)); return x; }'
+assert 3 '{ int x; asm("mov $3, %%ebx\n\tmov %%ebx, %0" : "=r"(x) : : "rbx"); return x; }'
+assert 6 '{ int x; asm("mov $6, %0" : "=r"(x) : : "rax", "rcx", "rdx", "rsi", "rdi", "r8", "r9", "r10", "r11"); return x; }'
+assert 2 '{ asm("nop"); return 2; }'
+assert 1 '{ asm volatile("" ::: "memory"); return 1; }'
+assert 15 '{ int x = 5, y = 10; asm("add %1, %0" : "+r"(x) : "r"(y) : "cc"); return x; }'
+assert 3 $'int main() {\n\tint x = 3;\n  return x;\n}'
+
 echo OK